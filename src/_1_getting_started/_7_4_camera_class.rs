@@ -20,7 +20,7 @@ use camera::Camera;
 use image;
 use image::GenericImage;
 
-use cgmath::{Matrix4, Vector3, vec3,  Deg, perspective, Point3};
+use cgmath::{Matrix4, Vector3, vec3, Deg, Point3};
 use cgmath::prelude::*;
 
 // settings
@@ -248,7 +248,7 @@ pub fn main_1_7_4() {
             ourShader.useProgram();
 
             // pass projection matrix to shader (note that in this case it could change every frame)
-            let projection: Matrix4<f32> = perspective(Deg(camera.Zoom), SCR_WIDTH as f32 / SCR_HEIGHT as f32 , 0.1, 100.0);
+            let projection: Matrix4<f32> = camera.GetProjectionMatrix();
             ourShader.setMat4(c_str!("projection"), &projection);
 
             // camera/view transformation
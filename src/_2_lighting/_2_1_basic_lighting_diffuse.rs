@@ -15,7 +15,7 @@ use common::{process_events, processInput};
 use shader::Shader;
 use camera::Camera;
 
-use cgmath::{Matrix4, vec3, Point3, Deg, perspective};
+use cgmath::{Matrix4, vec3, Point3};
 use cgmath::prelude::*;
 
 // settings
@@ -191,7 +191,7 @@ pub fn main_2_2_1() {
             lightingShader.setVector3(c_str!("lightPos"), &lightPos);
 
             // view/projection transformations
-            let projection: Matrix4<f32> = perspective(Deg(camera.Zoom), SCR_WIDTH as f32 / SCR_HEIGHT as f32, 0.1, 100.0);
+            let projection: Matrix4<f32> = camera.GetProjectionMatrix();
             let view = camera.GetViewMatrix();
             lightingShader.setMat4(c_str!("projection"), &projection);
             lightingShader.setMat4(c_str!("view"), &view);
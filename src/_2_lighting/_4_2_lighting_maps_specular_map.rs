@@ -15,7 +15,7 @@ use common::{process_events, processInput, loadTexture};
 use shader::Shader;
 use camera::Camera;
 
-use cgmath::{Matrix4, vec3, Point3, Deg, perspective};
+use cgmath::{Matrix4, vec3, Point3};
 use cgmath::prelude::*;
 
 // settings
@@ -210,7 +210,7 @@ pub fn main_2_4_2() {
             lightingShader.setFloat(c_str!("material.shininess"), 64.0);
 
             // view/projection transformations
-            let projection: Matrix4<f32> = perspective(Deg(camera.Zoom), SCR_WIDTH as f32 / SCR_HEIGHT as f32, 0.1, 100.0);
+            let projection: Matrix4<f32> = camera.GetProjectionMatrix();
             let view = camera.GetViewMatrix();
             lightingShader.setMat4(c_str!("projection"), &projection);
             lightingShader.setMat4(c_str!("view"), &view);
@@ -15,7 +15,7 @@ use common::{process_events, processInput, loadTexture};
 use shader::Shader;
 use camera::Camera;
 
-use cgmath::{Matrix4, Vector3, vec3, Point3, Deg, perspective};
+use cgmath::{Matrix4, Vector3, vec3, Point3, Deg};
 use cgmath::prelude::*;
 
 // settings
@@ -224,7 +224,7 @@ pub fn main_2_5_2() {
             lightingShader.setFloat(c_str!("material.shininess"), 32.0);
 
             // view/projection transformations
-            let projection: Matrix4<f32> = perspective(Deg(camera.Zoom), SCR_WIDTH as f32 / SCR_HEIGHT as f32, 0.1, 100.0);
+            let projection: Matrix4<f32> = camera.GetProjectionMatrix();
             let view = camera.GetViewMatrix();
             lightingShader.setMat4(c_str!("projection"), &projection);
             lightingShader.setMat4(c_str!("view"), &view);
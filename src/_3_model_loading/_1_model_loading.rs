@@ -9,20 +9,19 @@ use std::ffi::CStr;
 
 use common::{process_events, processInput};
 use shader::Shader;
-use camera::Camera;
+use orbit_camera::OrbitCamera;
 use model::Model;
 
-use cgmath::{Matrix4, vec3, Point3, Deg, perspective};
+use cgmath::{Matrix4, vec3};
 
 // settings
 const SCR_WIDTH: u32 = 800;
 const SCR_HEIGHT: u32 = 600;
 
 pub fn main_3_1() {
-    let mut camera = Camera {
-        Position: Point3::new(0.0, 0.0, 3.0),
-        ..Camera::default()
-    };
+    // NOTE: an orbit camera instead of the FPS-style one of the original: drag with the left mouse
+    // button to rotate around the model, with the middle button to pan and scroll to zoom.
+    let mut camera = OrbitCamera::default();
 
     let mut firstMouse = true;
     let mut lastX: f32 = SCR_WIDTH as f32 / 2.0;
@@ -50,9 +49,6 @@ pub fn main_3_1() {
     window.set_cursor_pos_polling(true);
     window.set_scroll_polling(true);
 
-    // tell GLFW to capture our mouse
-    window.set_cursor_mode(glfw::CursorMode::Disabled);
-
     // gl: load all OpenGL function pointers
     // ---------------------------------------
     gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
@@ -78,6 +74,14 @@ pub fn main_3_1() {
         (ourShader, ourModel)
     };
 
+    // the model matrix used below; translate it down so it's at the center of the scene
+    // and scale it down, it's a bit too big for our scene
+    let modelMatrix = Matrix4::<f32>::from_translation(vec3(0.0, -1.75, 0.0)) * Matrix4::from_scale(0.2);
+
+    // frame the (transformed) bounding box of the model
+    let bounds = ourModel.aabb.transform(&modelMatrix);
+    camera.Frame(bounds.min, bounds.max);
+
     // render loop
     // -----------
     while !window.should_close() {
@@ -105,15 +109,13 @@ pub fn main_3_1() {
             ourShader.useProgram();
 
             // view/projection transformations
-            let projection: Matrix4<f32> = perspective(Deg(camera.Zoom), SCR_WIDTH as f32 / SCR_HEIGHT as f32, 0.1, 100.0);
+            let projection: Matrix4<f32> = camera.GetProjectionMatrix();
             let view = camera.GetViewMatrix();
             ourShader.setMat4(c_str!("projection"), &projection);
             ourShader.setMat4(c_str!("view"), &view);
 
             // render the loaded model
-            let mut model = Matrix4::<f32>::from_translation(vec3(0.0, -1.75, 0.0)); // translate it down so it's at the center of the scene
-            model = model * Matrix4::from_scale(0.2);  // it's a bit too big for our scene, so scale it down
-            ourShader.setMat4(c_str!("model"), &model);
+            ourShader.setMat4(c_str!("model"), &modelMatrix);
             ourModel.Draw(&ourShader);
         }
 
@@ -11,10 +11,11 @@ use std::ffi::CStr;
 
 use common::{process_events, processInput};
 use shader::Shader;
-use camera::Camera;
+use camera::{Camera, Projection};
 use model::Model;
+use frustum::{CullStats, Frustum};
 
-use cgmath::{Matrix4, vec3, Point3, Deg, perspective};
+use cgmath::{Matrix4, vec3, Point3, Deg};
 use cgmath::prelude::*;
 
 // settings
@@ -24,6 +25,7 @@ const SCR_HEIGHT: u32 = 720;
 pub fn main_4_10_2() {
     let mut camera = Camera {
         Position: Point3::new(0.0, 0.0, 55.0),
+        Projection: Projection::Perspective { near: 0.1, far: 1000.0 },
         ..Camera::default()
     };
 
@@ -35,6 +37,10 @@ pub fn main_4_10_2() {
     let mut deltaTime: f32; // time between current frame and last frame
     let mut lastFrame: f32 = 0.0;
 
+    // NOTE: not in the original: rocks outside the view frustum are skipped, the window title shows how many
+    let mut cullStats = CullStats::default();
+    let mut lastTitleUpdate: f32 = 0.0;
+
     // glfw: initialize and configure
     // ------------------------------
     let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
@@ -132,7 +138,7 @@ pub fn main_4_10_2() {
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
 
             // configure transformation matrices
-            let projection: Matrix4<f32> = perspective(Deg(camera.Zoom), SCR_WIDTH as f32 / SCR_HEIGHT as f32, 0.1, 1000.0);
+            let projection: Matrix4<f32> = camera.GetProjectionMatrix();
             let view = camera.GetViewMatrix();
             shader.useProgram();
             shader.setMat4(c_str!("projection"), &projection);
@@ -145,11 +151,17 @@ pub fn main_4_10_2() {
             planet.Draw(&shader);
 
             // draw meteorites
+            let frustum = Frustum::from_matrix(&(projection * view));
+            cullStats.reset();
             for model in &modelMatrices {
                 shader.setMat4(c_str!("model"), model);
-                rock.Draw(&shader);
+                rock.DrawCulled(&shader, &frustum, model, &mut cullStats);
             }
         }
+        if currentFrame - lastTitleUpdate >= 1.0 {
+            window.set_title(&format!("LearnOpenGL - rock meshes {}", cullStats));
+            lastTitleUpdate = currentFrame;
+        }
 
         // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved etc.)
         // -------------------------------------------------------------------------------
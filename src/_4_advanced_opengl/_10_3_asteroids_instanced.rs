@@ -14,10 +14,11 @@ use std::ptr;
 
 use common::{process_events, processInput};
 use shader::Shader;
-use camera::Camera;
+use camera::{Camera, Projection};
 use model::Model;
+use frustum::{CullStats, Frustum};
 
-use cgmath::{Matrix4, vec3, Point3, Vector4, Deg, perspective};
+use cgmath::{Matrix4, vec3, Point3, Vector4, Deg};
 use cgmath::prelude::*;
 
 // settings
@@ -27,6 +28,7 @@ const SCR_HEIGHT: u32 = 720;
 pub fn main_4_10_3() {
     let mut camera = Camera {
         Position: Point3::new(0.0, 0.0, 155.0),
+        Projection: Projection::Perspective { near: 0.1, far: 1000.0 },
         ..Camera::default()
     };
 
@@ -38,6 +40,11 @@ pub fn main_4_10_3() {
     let mut deltaTime: f32; // time between current frame and last frame
     let mut lastFrame: f32 = 0.0;
 
+    // NOTE: not in the original: only the rocks inside the view frustum are copied to the instance
+    // buffer and drawn each frame, the window title shows how many were culled
+    let mut cullStats = CullStats::default();
+    let mut lastTitleUpdate: f32 = 0.0;
+
     // glfw: initialize and configure
     // ------------------------------
     let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
@@ -63,7 +70,7 @@ pub fn main_4_10_3() {
     // ---------------------------------------
     gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
 
-    let (asteroidShader, planetShader, rock, planet, amount) = unsafe {
+    let (asteroidShader, planetShader, rock, planet, modelMatrices, buffer) = unsafe {
         // configure global opengl state
         // -----------------------------
         gl::Enable(gl::DEPTH_TEST);
@@ -120,7 +127,7 @@ pub fn main_4_10_3() {
             gl::ARRAY_BUFFER,
             (amount * mem::size_of::<Matrix4<f32>>()) as isize,
             &modelMatrices[0] as *const Matrix4<f32> as *const c_void,
-            gl::STATIC_DRAW);
+            gl::DYNAMIC_DRAW);
 
         // set transformation matrices as an instance vertex attribute (with divisor 1)
         // note: we're cheating a little by taking the, now publicly declared, VAO of the model's mesh(es) and adding new vertexAttribPointers
@@ -149,8 +156,11 @@ pub fn main_4_10_3() {
             gl::BindVertexArray(0);
         }
 
-        (asteroidShader, planetShader, rock, planet, amount)
+        (asteroidShader, planetShader, rock, planet, modelMatrices, buffer)
     };
+    // bounding sphere of the rock model, in its local space
+    let rockBounds = rock.bounding_sphere;
+    let mut visibleMatrices: Vec<Matrix4<f32>> = Vec::with_capacity(modelMatrices.len());
 
     // render loop
     // -----------
@@ -176,7 +186,7 @@ pub fn main_4_10_3() {
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
 
             // configure transformation matrices
-            let projection: Matrix4<f32> = perspective(Deg(45.0), SCR_WIDTH as f32 / SCR_HEIGHT as f32, 0.1, 1000.0);
+            let projection: Matrix4<f32> = camera.GetProjectionMatrix();
             let view = camera.GetViewMatrix();
             asteroidShader.useProgram();
             asteroidShader.setMat4(c_str!("projection"), &projection);
@@ -197,12 +207,31 @@ pub fn main_4_10_3() {
             gl::ActiveTexture(gl::TEXTURE0);
             gl::BindTexture(gl::TEXTURE_2D, rock.textures_loaded[0].id); // note: we also made the textures_loaded vector public (instead of private) from the model class.
 
+            // compact the instance buffer to the visible rocks
+            let frustum = Frustum::from_matrix(&(projection * view));
+            cullStats.reset();
+            visibleMatrices.clear();
+            visibleMatrices.extend(modelMatrices.iter()
+                .filter(|model| cullStats.record(frustum.intersects_sphere(&rockBounds.transform(model)))));
+            if !visibleMatrices.is_empty() {
+                gl::BindBuffer(gl::ARRAY_BUFFER, buffer);
+                gl::BufferSubData(
+                    gl::ARRAY_BUFFER,
+                    0,
+                    (visibleMatrices.len() * mem::size_of::<Matrix4<f32>>()) as isize,
+                    &visibleMatrices[0] as *const Matrix4<f32> as *const c_void);
+            }
+
             for mesh in &rock.meshes {
                 gl::BindVertexArray(mesh.VAO);
-                gl::DrawElementsInstanced(gl::TRIANGLES, mesh.indices.len() as i32, gl::UNSIGNED_INT, ptr::null(), amount as i32);
+                gl::DrawElementsInstanced(gl::TRIANGLES, mesh.indices.len() as i32, gl::UNSIGNED_INT, ptr::null(), visibleMatrices.len() as i32);
                 gl::BindVertexArray(0);
             }
         }
+        if currentFrame - lastTitleUpdate >= 1.0 {
+            window.set_title(&format!("LearnOpenGL - asteroids {}", cullStats));
+            lastTitleUpdate = currentFrame;
+        }
 
         // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved etc.)
         // -------------------------------------------------------------------------------
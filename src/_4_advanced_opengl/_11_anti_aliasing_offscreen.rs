@@ -16,7 +16,7 @@ use common::{process_events, processInput};
 use shader::Shader;
 use camera::Camera;
 
-use cgmath::{Matrix4,  Deg, perspective, Point3};
+use cgmath::{Matrix4, Point3};
 use cgmath::prelude::*;
 
 // settings
@@ -243,7 +243,7 @@ pub fn main_4_11() {
 
             // set transformation matrices
             shader.useProgram();
-            let projection: Matrix4<f32> = perspective(Deg(camera.Zoom), SCR_WIDTH as f32 / SCR_HEIGHT as f32 , 0.1, 100.0);
+            let projection: Matrix4<f32> = camera.GetProjectionMatrix();
             shader.setMat4(c_str!("projection"), &projection);
             shader.setMat4(c_str!("view"), &camera.GetViewMatrix());
             shader.setMat4(c_str!("model"), &Matrix4::identity());
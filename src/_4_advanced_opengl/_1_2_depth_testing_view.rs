@@ -16,7 +16,7 @@ use common::{process_events, processInput, loadTexture};
 use shader::Shader;
 use camera::Camera;
 
-use cgmath::{Matrix4, vec3, Deg, perspective, Point3};
+use cgmath::{Matrix4, vec3, Point3};
 use cgmath::prelude::*;
 
 // settings
@@ -167,11 +167,6 @@ pub fn main_4_1_2() {
         let cubeTexture = loadTexture("resources/textures/marble.jpg");
         let floorTexture = loadTexture("resources/textures/metal.png");
 
-        // shader configuration
-        // --------------------
-        shader.useProgram();
-        shader.setInt(c_str!("texture1"), 0);
-
         (shader, cubeVBO, cubeVAO, planeVBO, planeVAO, cubeTexture, floorTexture)
     };
 
@@ -201,7 +196,7 @@ pub fn main_4_1_2() {
             shader.useProgram();
             let mut model: Matrix4<f32>;
             let view = camera.GetViewMatrix();
-            let projection: Matrix4<f32> = perspective(Deg(camera.Zoom), SCR_WIDTH as f32 / SCR_HEIGHT as f32 , 0.1, 100.0);
+            let projection: Matrix4<f32> = camera.GetProjectionMatrix();
             shader.setMat4(c_str!("view"), &view);
             shader.setMat4(c_str!("projection"), &projection);
             // cubes
@@ -16,7 +16,7 @@ use common::{process_events, processInput, loadTexture};
 use shader::Shader;
 use camera::Camera;
 
-use cgmath::{Matrix4, vec3, Deg, perspective, Point3};
+use cgmath::{Matrix4, vec3, Point3};
 use cgmath::prelude::*;
 
 // settings
@@ -211,7 +211,7 @@ pub fn main_4_2() {
             shaderSingleColor.useProgram();
             let mut model: Matrix4<f32>;
             let view = camera.GetViewMatrix();
-            let projection: Matrix4<f32> = perspective(Deg(camera.Zoom), SCR_WIDTH as f32 / SCR_HEIGHT as f32 , 0.1, 100.0);
+            let projection: Matrix4<f32> = camera.GetProjectionMatrix();
             shaderSingleColor.setMat4(c_str!("view"), &view);
             shaderSingleColor.setMat4(c_str!("projection"), &projection);
 
@@ -17,7 +17,7 @@ use common::{process_events, processInput};
 use shader::Shader;
 use camera::Camera;
 
-use cgmath::{Matrix4, vec3,  Deg, perspective, Point3};
+use cgmath::{Matrix4, vec3, Point3};
 use cgmath::prelude::*;
 
 use image;
@@ -239,7 +239,7 @@ pub fn main_4_3_1() {
 
             // draw objects
             shader.useProgram();
-            let projection: Matrix4<f32> = perspective(Deg(camera.Zoom), SCR_WIDTH as f32 / SCR_HEIGHT as f32 , 0.1, 100.0);
+            let projection: Matrix4<f32> = camera.GetProjectionMatrix();
             let view = camera.GetViewMatrix();
             let mut model: Matrix4<f32>;
             shader.setMat4(c_str!("projection"), &projection);
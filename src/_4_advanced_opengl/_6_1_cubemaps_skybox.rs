@@ -13,7 +13,7 @@ use self::glfw::Context;
 extern crate gl;
 use self::gl::types::*;
 
-use cgmath::{Matrix4,  Deg, perspective, Point3};
+use cgmath::{Matrix4, Point3};
 use cgmath::prelude::*;
 
 use image;
@@ -253,7 +253,7 @@ pub fn main_4_6_1() {
             shader.useProgram();
             let model: Matrix4<f32> = Matrix4::identity();
             let mut view = camera.GetViewMatrix();
-            let projection: Matrix4<f32> = perspective(Deg(camera.Zoom), SCR_WIDTH as f32 / SCR_HEIGHT as f32 , 0.1, 100.0);
+            let projection: Matrix4<f32> = camera.GetProjectionMatrix();
             shader.setMat4(c_str!("model"), &model);
             shader.setMat4(c_str!("view"), &view);
             shader.setMat4(c_str!("projection"), &projection);
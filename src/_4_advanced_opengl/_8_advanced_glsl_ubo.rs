@@ -12,17 +12,25 @@ use self::glfw::Context;
 extern crate gl;
 use self::gl::types::*;
 
-use cgmath::{Matrix4, vec3, Deg, perspective, Point3};
-use cgmath::prelude::*;
+use cgmath::{Matrix4, vec3, Point3};
 
 use common::{process_events, processInput};
 use shader::Shader;
 use camera::Camera;
+use uniform_buffer::UniformBuffer;
 
 // settings
 const SCR_WIDTH: u32 = 1280;
 const SCR_HEIGHT: u32 = 720;
 
+uniform_block! {
+    /// the `Matrices` uniform block of 8.advanced_glsl.vs
+    struct Matrices {
+        projection: Matrix4<f32>,
+        view: Matrix4<f32>,
+    }
+}
+
 pub fn main_4_8() {
     let mut camera = Camera {
         Position: Point3::new(0.0, 0.0, 3.0),
@@ -136,29 +144,12 @@ pub fn main_4_8() {
 
         // configure a uniform buffer object
         // ---------------------------------
-        // first. We get the relevant block indices
-        let uniformBlockIndexRed = gl::GetUniformBlockIndex(shaderRed.ID, c_str!("Matrices").as_ptr());
-        let uniformBlockIndexGreen = gl::GetUniformBlockIndex(shaderGreen.ID, c_str!("Matrices").as_ptr());
-        let uniformBlockIndexBlue = gl::GetUniformBlockIndex(shaderBlue.ID, c_str!("Matrices").as_ptr());
-        let uniformBlockIndexYellow = gl::GetUniformBlockIndex(shaderYellow.ID, c_str!("Matrices").as_ptr());
+        // create the buffer and attach it to uniform binding point 0
+        let uboMatrices = UniformBuffer::<Matrices>::new(0);
         // then we link each shader's uniform block to this uniform binding point
-        gl::UniformBlockBinding(shaderRed.ID, uniformBlockIndexRed, 0);
-        gl::UniformBlockBinding(shaderGreen.ID, uniformBlockIndexGreen, 0);
-        gl::UniformBlockBinding(shaderBlue.ID, uniformBlockIndexBlue, 0);
-        gl::UniformBlockBinding(shaderYellow.ID, uniformBlockIndexYellow, 0);
-        // Now actually create the buffer
-        let mut uboMatrices = 0;
-        gl::GenBuffers(1, &mut uboMatrices);
-        gl::BindBuffer(gl::UNIFORM_BUFFER, uboMatrices);
-        gl::BufferData(gl::UNIFORM_BUFFER, 2 * mem::size_of::<Matrix4<f32>>() as isize, ptr::null(), gl::STATIC_DRAW);
-        // define the range of the buffer that links to a uniform binding point
-        gl::BindBufferRange(gl::UNIFORM_BUFFER, 0, uboMatrices, 0, 2 * 2 * mem::size_of::<Matrix4<f32>>() as isize);
-
-        // store the projection matrix (we only do this once now) (note: we're not using zoom anymore by changing the FoV)
-        let projection: Matrix4<f32> = perspective(Deg(45.0), SCR_WIDTH as f32 / SCR_HEIGHT as f32 , 0.1, 100.0);
-        gl::BindBuffer(gl::UNIFORM_BUFFER, uboMatrices);
-        gl::BufferSubData(gl::UNIFORM_BUFFER, 0, mem::size_of::<Matrix4<f32>>() as isize, projection.as_ptr() as *const c_void);
-        gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+        for shader in &[&shaderRed, &shaderGreen, &shaderBlue, &shaderYellow] {
+            uboMatrices.bind_block(shader, c_str!("Matrices")).unwrap_or_else(|err| panic!("{}", err));
+        }
 
         (shaderRed, shaderGreen, shaderBlue, shaderYellow, cubeVBO, cubeVAO, uboMatrices)
     };
@@ -187,11 +178,10 @@ pub fn main_4_8() {
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
 
             // set the view and projection matrix in the uniform block - we only have to do this once per loop iteration.
-            let view = camera.GetViewMatrix();
-            gl::BindBuffer(gl::UNIFORM_BUFFER, uboMatrices);
-            let size = mem::size_of::<Matrix4<f32>>() as isize;
-            gl::BufferSubData(gl::UNIFORM_BUFFER, size, size, view.as_ptr() as *const c_void);
-            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+            uboMatrices.update(&Matrices {
+                projection: camera.GetProjectionMatrix(),
+                view: camera.GetViewMatrix(),
+            });
 
             // draw 4 cubes
             // RED
@@ -228,5 +218,6 @@ pub fn main_4_8() {
     unsafe {
         gl::DeleteVertexArrays(1, &cubeVAO);
         gl::DeleteBuffers(1, &cubeVBO);
+        uboMatrices.delete();
     }
 }
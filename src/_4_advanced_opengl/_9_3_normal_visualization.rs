@@ -8,7 +8,7 @@ use self::glfw::Context;
 
 extern crate gl;
 
-use cgmath::{Matrix4, Deg, vec3, Point3, perspective};
+use cgmath::{Matrix4, vec3, Point3};
 
 use common::{process_events, processInput};
 use shader::Shader;
@@ -106,7 +106,7 @@ pub fn main_4_9_3() {
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
 
             // configure transformation matrices
-            let projection: Matrix4<f32> = perspective(Deg(45.0), SCR_WIDTH as f32 / SCR_HEIGHT as f32, 0.1, 100.0);
+            let projection: Matrix4<f32> = camera.GetProjectionMatrix();
             let view = camera.GetViewMatrix();
             let mut model = Matrix4::<f32>::from_translation(vec3(0.0, -1.75, 0.0)); // translate it down so it's at the center of the scene
             model = model * Matrix4::from_scale(0.2);  // it's a bit too big for our scene, so scale it down
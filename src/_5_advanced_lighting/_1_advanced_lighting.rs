@@ -2,7 +2,7 @@
 #![allow(non_snake_case)]
 
 extern crate glfw;
-use self::glfw::{Context, Key, Action};
+use self::glfw::Context;
 
 extern crate gl;
 use self::gl::types::*;
@@ -12,12 +12,14 @@ use std::mem;
 use std::os::raw::c_void;
 use std::ffi::CStr;
 
+use common;
 use common::{process_events, loadTexture};
-use shader::Shader;
+use input;
+use shader::ShaderBuilder;
+use shader_permutations::ShaderPermutations;
 use camera::Camera;
-use camera::Camera_Movement::*;
 
-use cgmath::{Matrix4, vec3,  Deg, perspective, Point3};
+use cgmath::{Matrix4, vec3, Point3};
 use cgmath::prelude::*;
 
 // settings
@@ -26,7 +28,6 @@ const SCR_HEIGHT: u32 = 720;
 
 pub fn main_5_1() {
     let mut blinn = false;
-    let mut blinnKeyPressed = false;
 
     let mut camera = Camera {
         Position: Point3::new(0.0, 0.0, 3.0),
@@ -66,18 +67,20 @@ pub fn main_5_1() {
     // ---------------------------------------
     gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
 
-    let (shader, planeVBO, planeVAO, floorTexture) = unsafe {
+    let (mut shaders, planeVBO, planeVAO, floorTexture) = unsafe {
         // configure global opengl state
         // -----------------------------
         gl::Enable(gl::DEPTH_TEST);
         gl::Enable(gl::BLEND);
         gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
 
-        // build and compile shaders
+        // build and compile shaders: one program variant for Phong and one with BLINN defined
         // ------------------------------------
-        let shader = Shader::new(
-            "src/_5_advanced_lighting/shaders/1.advanced_lighting.vs",
-            "src/_5_advanced_lighting/shaders/1.advanced_lighting.fs");
+        let mut shaders = ShaderPermutations::new(ShaderBuilder::new()
+            .vertex_file("src/_5_advanced_lighting/shaders/1.advanced_lighting.vs")
+            .fragment_file("src/_5_advanced_lighting/shaders/1.advanced_lighting.fs")
+            .env_cache());
+        shaders.precompile(&[&[], &[("BLINN", "")]]).unwrap_or_else(|err| panic!("{}", err));
 
         // set up vertex data (and buffer(s)) and configure vertex attributes
         // ------------------------------------------------------------------
@@ -116,10 +119,12 @@ pub fn main_5_1() {
 
         // shader configuration
         // --------------------
-        shader.useProgram();
-        shader.setInt(c_str!("texture1"), 0);
+        for (_, shader) in shaders.compiled() {
+            shader.useProgram();
+            shader.setInt(c_str!("floorTexture"), 0);
+        }
 
-        (shader, planeVBO, planeVAO, floorTexture)
+        (shaders, planeVBO, planeVAO, floorTexture)
     };
 
     // lighting info
@@ -141,7 +146,7 @@ pub fn main_5_1() {
 
         // input
         // -----
-        processInput(&mut window, deltaTime, &mut camera, &mut blinn, &mut blinnKeyPressed);
+        processInput(&mut window, deltaTime, &mut camera, &mut blinn);
 
         // render
         // ------
@@ -150,15 +155,15 @@ pub fn main_5_1() {
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
 
             // draw objects
+            let shader = shaders.get(if blinn { &[("BLINN", "")] } else { &[] }).unwrap();
             shader.useProgram();
-            let projection: Matrix4<f32> = perspective(Deg(camera.Zoom), SCR_WIDTH as f32 / SCR_HEIGHT as f32 , 0.1, 100.0);
+            let projection: Matrix4<f32> = camera.GetProjectionMatrix();
             let view = camera.GetViewMatrix();
             shader.setMat4(c_str!("projection"), &projection);
             shader.setMat4(c_str!("view"), &view);
             // set light uniforms
             shader.setVector3(c_str!("viewPos"), &camera.Position.to_vec());
             shader.setVector3(c_str!("lightPos"), &lightPos);
-            shader.setInt(c_str!("blinn"), blinn as i32);
             // floor
             gl::BindVertexArray(planeVAO);
             gl::ActiveTexture(gl::TEXTURE0);
@@ -180,31 +185,12 @@ pub fn main_5_1() {
     }
 }
 
-// NOTE: not the same version as in common.rs
-pub fn processInput(window: &mut glfw::Window, deltaTime: f32, camera: &mut Camera, blinn: &mut bool, blinnKeyPressed: &mut bool) {
-    if window.get_key(Key::Escape) == Action::Press {
-        window.set_should_close(true)
-    }
+// NOTE: extends the version in common.rs
+pub fn processInput(window: &mut glfw::Window, deltaTime: f32, camera: &mut Camera, blinn: &mut bool) {
+    common::processInput(window, deltaTime, camera);
 
-    if window.get_key(Key::W) == Action::Press {
-        camera.ProcessKeyboard(FORWARD, deltaTime);
-    }
-    if window.get_key(Key::S) == Action::Press {
-        camera.ProcessKeyboard(BACKWARD, deltaTime);
-    }
-    if window.get_key(Key::A) == Action::Press {
-        camera.ProcessKeyboard(LEFT, deltaTime);
-    }
-    if window.get_key(Key::D) == Action::Press {
-        camera.ProcessKeyboard(RIGHT, deltaTime);
-    }
-
-    if window.get_key(Key::B) == Action::Press && !(*blinnKeyPressed) {
+    if input::pressed("toggle_blinn") {
         *blinn = !(*blinn);
-        *blinnKeyPressed = true;
         println!("{}", if *blinn { "Blinn-Phong" } else { "Phong" })
     }
-    if window.get_key(Key::B) == Action::Release {
-        *blinnKeyPressed = false;
-    }
 }
@@ -2,7 +2,7 @@
 #![allow(non_snake_case)]
 
 extern crate glfw;
-use self::glfw::{Context, Key, Action};
+use self::glfw::Context;
 
 extern crate gl;
 use self::gl::types::*;
@@ -17,12 +17,13 @@ use image;
 use image::GenericImage;
 use image::DynamicImage::*;
 
+use common;
 use common::process_events;
+use input;
 use shader::Shader;
 use camera::Camera;
-use camera::Camera_Movement::*;
 
-use cgmath::{Matrix4, vec3, Vector3, Deg, perspective, Point3};
+use cgmath::{Matrix4, vec3, Vector3, Point3};
 use cgmath::prelude::*;
 
 // settings
@@ -31,7 +32,6 @@ const SCR_HEIGHT: u32 = 720;
 
 pub fn main_5_2() {
     let mut gammaEnabled = false;
-    let mut gammaKeyPressed = false;
 
     let mut camera = Camera {
         Position: Point3::new(0.0, 0.0, 3.0),
@@ -158,7 +158,7 @@ pub fn main_5_2() {
 
         // input
         // -----
-        processInput(&mut window, deltaTime, &mut camera, &mut gammaEnabled, &mut gammaKeyPressed);
+        processInput(&mut window, deltaTime, &mut camera, &mut gammaEnabled);
 
         // render
         // ------
@@ -168,7 +168,7 @@ pub fn main_5_2() {
 
             // draw objects
             shader.useProgram();
-            let projection: Matrix4<f32> = perspective(Deg(camera.Zoom), SCR_WIDTH as f32 / SCR_HEIGHT as f32 , 0.1, 100.0);
+            let projection: Matrix4<f32> = camera.GetProjectionMatrix();
             let view = camera.GetViewMatrix();
             shader.setMat4(c_str!("projection"), &projection);
             shader.setMat4(c_str!("view"), &view);
@@ -204,33 +204,14 @@ pub fn main_5_2() {
     }
 }
 
-// NOTE: not the same version as in common.rs
-pub fn processInput(window: &mut glfw::Window, deltaTime: f32, camera: &mut Camera, gammaEnabled: &mut bool, gammaKeyPressed: &mut bool) {
-    if window.get_key(Key::Escape) == Action::Press {
-        window.set_should_close(true)
-    }
-
-    if window.get_key(Key::W) == Action::Press {
-        camera.ProcessKeyboard(FORWARD, deltaTime);
-    }
-    if window.get_key(Key::S) == Action::Press {
-        camera.ProcessKeyboard(BACKWARD, deltaTime);
-    }
-    if window.get_key(Key::A) == Action::Press {
-        camera.ProcessKeyboard(LEFT, deltaTime);
-    }
-    if window.get_key(Key::D) == Action::Press {
-        camera.ProcessKeyboard(RIGHT, deltaTime);
-    }
+// NOTE: extends the version in common.rs
+pub fn processInput(window: &mut glfw::Window, deltaTime: f32, camera: &mut Camera, gammaEnabled: &mut bool) {
+    common::processInput(window, deltaTime, camera);
 
-    if window.get_key(Key::Space) == Action::Press && !(*gammaKeyPressed) {
+    if input::pressed("toggle_gamma") {
         *gammaEnabled = !(*gammaEnabled);
-        *gammaKeyPressed = true;
         println!("{}", if *gammaEnabled { "Gamma Enabled" } else { "Gamma disabled" })
     }
-    if window.get_key(Key::Space) == Action::Release {
-        *gammaKeyPressed = false;
-    }
 }
 
 // NOTE: not the same version as in common.rs
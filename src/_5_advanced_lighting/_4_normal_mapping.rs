@@ -16,7 +16,7 @@ use common::{process_events, processInput, loadTexture};
 use shader::Shader;
 use camera::Camera;
 
-use cgmath::{Matrix4, vec3, Vector3, vec2, Vector2, Deg, perspective, Point3};
+use cgmath::{Matrix4, vec3, Vector3, vec2, Vector2, Deg, Point3};
 use cgmath::prelude::*;
 
 // settings
@@ -118,7 +118,7 @@ pub fn main_5_4() {
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
 
              // configure view/projection matrices
-            let projection: Matrix4<f32> = perspective(Deg(camera.Zoom), SCR_WIDTH as f32 / SCR_HEIGHT as f32 , 0.1, 100.0);
+            let projection: Matrix4<f32> = camera.GetProjectionMatrix();
             let view = camera.GetViewMatrix();
             shader.useProgram();
             shader.setMat4(c_str!("projection"), &projection);
@@ -2,7 +2,7 @@
 #![allow(non_snake_case)]
 
 extern crate glfw;
-use self::glfw::{Context, Key, Action};
+use self::glfw::Context;
 
 extern crate gl;
 use self::gl::types::*;
@@ -17,12 +17,13 @@ use image;
 use image::GenericImage;
 use image::DynamicImage::*;
 
+use common;
 use common::{process_events};
+use input;
 use shader::Shader;
 use camera::Camera;
-use camera::Camera_Movement::*;
 
-use cgmath::{Matrix4, vec3, Vector3, Deg, perspective, Point3};
+use cgmath::{Matrix4, vec3, Vector3, Point3};
 use cgmath::prelude::*;
 
 // settings
@@ -31,7 +32,6 @@ const SCR_HEIGHT: u32 = 720;
 
 pub fn main_5_6() {
     let mut hdr = true;
-    let mut hdrKeyPressed = false;
     let mut exposure: f32 = 1.0;
 
     let mut camera = Camera {
@@ -162,7 +162,7 @@ pub fn main_5_6() {
 
         // input
         // -----
-        processInput(&mut window, deltaTime, &mut camera, &mut hdr, &mut hdrKeyPressed, &mut exposure);
+        processInput(&mut window, deltaTime, &mut camera, &mut hdr, &mut exposure);
 
         // render
         // ------
@@ -174,7 +174,7 @@ pub fn main_5_6() {
             // -----------------------------------------------
             gl::BindFramebuffer(gl::FRAMEBUFFER, hdrFBO);
                 gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
-                let projection: Matrix4<f32> = perspective(Deg(camera.Zoom), SCR_WIDTH as f32 / SCR_HEIGHT as f32 , 0.1, 100.0);
+                let projection: Matrix4<f32> = camera.GetProjectionMatrix();
                 let view = camera.GetViewMatrix();
                 shader.useProgram();
                 shader.setMat4(c_str!("projection"), &projection);
@@ -321,38 +321,19 @@ unsafe fn renderQuad(quadVAO: &mut u32, quadVBO: &mut u32) {
     gl::BindVertexArray(0);
 }
 
-// NOTE: not the same version as in common.rs
+// NOTE: extends the version in common.rs
 pub fn processInput(
     window: &mut glfw::Window, deltaTime: f32, camera: &mut Camera,
-    hdr: &mut bool, hdrKeyPressed: &mut bool, exposure: &mut f32)
+    hdr: &mut bool, exposure: &mut f32)
 {
-    if window.get_key(Key::Escape) == Action::Press {
-        window.set_should_close(true)
-    }
-
-    if window.get_key(Key::W) == Action::Press {
-        camera.ProcessKeyboard(FORWARD, deltaTime);
-    }
-    if window.get_key(Key::S) == Action::Press {
-        camera.ProcessKeyboard(BACKWARD, deltaTime);
-    }
-    if window.get_key(Key::A) == Action::Press {
-        camera.ProcessKeyboard(LEFT, deltaTime);
-    }
-    if window.get_key(Key::D) == Action::Press {
-        camera.ProcessKeyboard(RIGHT, deltaTime);
-    }
+    common::processInput(window, deltaTime, camera);
 
-    if window.get_key(Key::Space) == Action::Press && !(*hdrKeyPressed) {
+    if input::pressed("toggle_hdr") {
         *hdr = !(*hdr);
-        *hdrKeyPressed = true;
         println!("hdr: {} | exposure: {}", if *hdr { "on" } else { "off" }, *exposure);
     }
-    if window.get_key(Key::Space) == Action::Release {
-        *hdrKeyPressed = false;
-    }
 
-    if window.get_key(Key::Q) == Action::Press {
+    if input::held("exposure_down") {
         if *exposure > 0.0 {
             *exposure -= 0.01;
         }
@@ -361,7 +342,7 @@ pub fn processInput(
         }
         println!("hdr: {} | exposure: {}", if *hdr { "on" } else { "off" }, *exposure);
     }
-    if window.get_key(Key::E) == Action::Press {
+    if input::held("exposure_up") {
         *exposure += 0.01;
         println!("hdr: {} | exposure: {}", if *hdr { "on" } else { "off" }, *exposure);
     }
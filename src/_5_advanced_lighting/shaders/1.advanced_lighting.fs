@@ -10,7 +10,6 @@ in VS_OUT {
 uniform sampler2D floorTexture;
 uniform vec3 lightPos;
 uniform vec3 viewPos;
-uniform bool blinn;
 
 void main()
 {
@@ -26,16 +25,13 @@ void main()
     vec3 viewDir = normalize(viewPos - fs_in.FragPos);
     vec3 reflectDir = reflect(-lightDir, normal);
     float spec = 0.0;
-    if(blinn)
-    {
-        vec3 halfwayDir = normalize(lightDir + viewDir);
-        spec = pow(max(dot(normal, halfwayDir), 0.0), 32.0);
-    }
-    else
-    {
-        vec3 reflectDir = reflect(-lightDir, normal);
-        spec = pow(max(dot(viewDir, reflectDir), 0.0), 8.0);
-    }
+    // compiled as a separate program variant with BLINN defined
+#ifdef BLINN
+    vec3 halfwayDir = normalize(lightDir + viewDir);
+    spec = pow(max(dot(normal, halfwayDir), 0.0), 32.0);
+#else
+    spec = pow(max(dot(viewDir, reflectDir), 0.0), 8.0);
+#endif
     vec3 specular = vec3(0.3) * spec; // assuming bright white light color
     FragColor = vec4(ambient + diffuse + specular, 1.0);
 }
@@ -11,14 +11,14 @@ extern crate num;
 use std::ptr;
 use std::mem::size_of;
 use std::os::raw::c_void;
-use std::ffi::{CStr, CString};
+use std::ffi::CStr;
 use std::f32::consts::PI;
 
 use common::{process_events, processInput};
 use shader::Shader;
 use camera::Camera;
 
-use cgmath::{Matrix4, vec3, Vector3, vec2, Deg, perspective, Point3};
+use cgmath::{Matrix4, vec3, Vector3, vec2, Point3};
 use cgmath::prelude::*;
 
 // settings
@@ -82,7 +82,7 @@ pub fn main_6_1_1() {
 
         // initialize static shader uniforms before rendering
         // --------------------------------------------------
-        let projection: Matrix4<f32> = perspective(Deg(camera.Zoom), SCR_WIDTH as f32 / SCR_HEIGHT as f32 , 0.1, 100.0);
+        let projection: Matrix4<f32> = camera.GetProjectionMatrix();
         shader.setMat4(c_str!("projection"), &projection);
 
         shader
@@ -159,16 +159,13 @@ pub fn main_6_1_1() {
             // render light source (simply re-render sphere at light positions)
             // this looks a bit off as we use the same shader, but it'll make their positions obvious and
             // keeps the codeprint small.
-            for (i, lightPosition) in lightPositions.iter().enumerate() {
-                // NOTE: toggle comments on next two lines to animate the lights
-                // let newPos = lightPosition + vec3((glfw.get_time() as f32 * 5.0).sin() * 5.0, 0.0, 0.0);
-                let newPos = *lightPosition;
-                let mut name = CString::new(format!("lightPositions[{}]", i)).unwrap();
-                shader.setVector3(&name, &newPos);
-                name = CString::new(format!("lightColors[{}]", i)).unwrap();
-                shader.setVector3(&name, &lightColors[i]);
-
-                model = Matrix4::from_translation(newPos);
+            // NOTE: toggle comments on next two lines to animate the lights
+            // let newPositions: Vec<Vector3<f32>> = lightPositions.iter().map(|p| p + vec3((glfw.get_time() as f32 * 5.0).sin() * 5.0, 0.0, 0.0)).collect();
+            let newPositions = lightPositions;
+            shader.set(c_str!("lightPositions"), &newPositions[..]);
+            shader.set(c_str!("lightColors"), &lightColors[..]);
+            for newPos in newPositions.iter() {
+                model = Matrix4::from_translation(*newPos);
                 model = model * Matrix4::from_scale(0.5);
                 shader.setMat4(c_str!("model"), &model);
                 renderSphere(&mut sphereVAO, &mut indexCount);
@@ -11,14 +11,14 @@ extern crate num;
 use std::ptr;
 use std::mem::size_of;
 use std::os::raw::c_void;
-use std::ffi::{CStr, CString};
+use std::ffi::CStr;
 use std::f32::consts::PI;
 
 use common::{process_events, processInput, loadTexture};
 use shader::Shader;
 use camera::Camera;
 
-use cgmath::{Matrix4, vec3, Vector3, vec2, Deg, perspective, Point3};
+use cgmath::{Matrix4, vec3, Vector3, vec2, Point3};
 use cgmath::prelude::*;
 
 // settings
@@ -95,7 +95,7 @@ pub fn main_6_1_2() {
 
         // initialize static shader uniforms before rendering
         // --------------------------------------------------
-        let projection: Matrix4<f32> = perspective(Deg(camera.Zoom), SCR_WIDTH as f32 / SCR_HEIGHT as f32 , 0.1, 100.0);
+        let projection: Matrix4<f32> = camera.GetProjectionMatrix();
         shader.setMat4(c_str!("projection"), &projection);
 
         (shader, albedo, normal, metallic, roughness, ao)
@@ -155,15 +155,10 @@ pub fn main_6_1_2() {
             gl::ActiveTexture(gl::TEXTURE4);
             gl::BindTexture(gl::TEXTURE_2D, ao);
 
-            // render rows*column number of spheres with varying metallic/roughness values scaled by rows and columns respectively
+            // render rows*column number of spheres (metallic/roughness come from the textures)
             let mut model: Matrix4<f32>;
             for row in 0..nrRows {
-                shader.setFloat(c_str!("metallic"), row as i32 as f32 / nrRows as f32);
                 for col in 0..nrColumns {
-                    // we clamp the roughness to 0.025 - 1.0 as perfectly smooth surfaces (roughness of 0.0) tend to look a bit off
-                    // on direct lighting.
-                    shader.setFloat(c_str!("roughness"), num::clamp(col as i32 as f32 / nrColumns as f32, 0.05, 1.0));
-
                     let model = Matrix4::from_translation(vec3(
                         (col - (nrColumns / 2)) as f32 * spacing,
                         (row - (nrRows / 2)) as f32 * spacing,
@@ -177,16 +172,13 @@ pub fn main_6_1_2() {
             // render light source (simply re-render sphere at light positions)
             // this looks a bit off as we use the same shader, but it'll make their positions obvious and
             // keeps the codeprint small.
-            for (i, lightPosition) in lightPositions.iter().enumerate() {
-                // NOTE: toggle comments on next two lines to animate the lights
-                // let newPos = lightPosition + vec3((glfw.get_time() as f32 * 5.0).sin() * 5.0, 0.0, 0.0);
-                let newPos = *lightPosition;
-                let mut name = CString::new(format!("lightPositions[{}]", i)).unwrap();
-                shader.setVector3(&name, &newPos);
-                name = CString::new(format!("lightColors[{}]", i)).unwrap();
-                shader.setVector3(&name, &lightColors[i]);
-
-                model = Matrix4::from_translation(newPos);
+            // NOTE: toggle comments on next two lines to animate the lights
+            // let newPositions: Vec<Vector3<f32>> = lightPositions.iter().map(|p| p + vec3((glfw.get_time() as f32 * 5.0).sin() * 5.0, 0.0, 0.0)).collect();
+            let newPositions = lightPositions;
+            shader.set(c_str!("lightPositions"), &newPositions[..]);
+            shader.set(c_str!("lightColors"), &lightColors[..]);
+            for newPos in newPositions.iter() {
+                model = Matrix4::from_translation(*newPos);
                 model = model * Matrix4::from_scale(0.5);
                 shader.setMat4(c_str!("model"), &model);
                 renderSphere(&mut sphereVAO, &mut indexCount);
@@ -0,0 +1,90 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// Camera bookmarks: numbered viewpoints that survive restarts, e.g. to get back to a view that
+/// shows a rendering bug. `common::processInput` wires them up for every tutorial with a camera
+/// that supports them (`Camera`, `QuatCamera`): 1-9 jump to a bookmark, Left Control + 1-9 stores
+/// the current view in that slot. Every change is written to `bookmarks.txt` right away.
+/// `cargo run 4_10_3 --bookmark 3` starts a tutorial at bookmark 3.
+///
+/// The file has one bookmark per line: slot, position x y z, yaw, pitch (degrees), zoom, movement speed.
+/// Empty lines and lines starting with `#` are ignored.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use cgmath;
+
+type Point3 = cgmath::Point3<f32>;
+
+pub const BOOKMARK_FILE: &str = "bookmarks.txt";
+/// slots 1 to `SLOTS` have actions (`bookmark_1`, ...), the file may contain any slot number
+pub const SLOTS: u32 = 9;
+
+/// The camera state a bookmark restores; the fields mean the same as in `Camera`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bookmark {
+    pub position: Point3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub zoom: f32,
+    pub speed: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Bookmarks {
+    pub slots: BTreeMap<u32, Bookmark>,
+}
+
+impl Bookmarks {
+    pub fn Get(&self, slot: u32) -> Option<Bookmark> {
+        self.slots.get(&slot).cloned()
+    }
+
+    pub fn Set(&mut self, slot: u32, bookmark: Bookmark) {
+        self.slots.insert(slot, bookmark);
+    }
+
+    pub fn Save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        writeln!(file, "# slot, position x y z, yaw, pitch, zoom, speed")?;
+        for (slot, b) in &self.slots {
+            let p = b.position;
+            writeln!(file, "{} {} {} {} {} {} {} {}", slot, p.x, p.y, p.z, b.yaw, b.pitch, b.zoom, b.speed)?;
+        }
+        Ok(())
+    }
+
+    /// loads a file written by `Save`; later lines win if a slot appears twice
+    pub fn Load<P: AsRef<Path>>(path: P) -> io::Result<Bookmarks> {
+        let mut bookmarks = Bookmarks::default();
+        for (lineNr, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut values = line.split_whitespace();
+            let slot = values.next().unwrap().parse::<u32>()
+                .map_err(|err| invalidData(lineNr, &format!("invalid slot: {}", err)))?;
+            let values: Vec<f32> = values.map(|v| v.parse::<f32>()).collect::<Result<_, _>>()
+                .map_err(|err| invalidData(lineNr, &err.to_string()))?;
+            if values.len() != 7 {
+                return Err(invalidData(lineNr, &format!("expected slot and 7 numbers, found {} numbers", values.len())));
+            }
+            bookmarks.Set(slot, Bookmark {
+                position: Point3::new(values[0], values[1], values[2]),
+                yaw: values[3],
+                pitch: values[4],
+                zoom: values[5],
+                speed: values[6],
+            });
+        }
+        Ok(bookmarks)
+    }
+}
+
+fn invalidData(lineNr: usize, message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", lineNr + 1, message))
+}
@@ -0,0 +1,131 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// Bounding volumes: axis-aligned boxes and spheres, e.g. for frustum culling (see `frustum`)
+
+use std::f32;
+
+use cgmath;
+use cgmath::prelude::*;
+
+type Point3 = cgmath::Point3<f32>;
+type Vector3 = cgmath::Vector3<f32>;
+type Matrix4 = cgmath::Matrix4<f32>;
+
+/// Axis-aligned bounding box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Point3,
+    pub max: Point3,
+}
+
+impl Default for Aabb {
+    fn default() -> Aabb {
+        Aabb::empty()
+    }
+}
+
+impl Aabb {
+    /// contains nothing, grows to the first point/box added with `extend`/`union`
+    pub fn empty() -> Aabb {
+        Aabb {
+            min: Point3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: Point3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        }
+    }
+
+    pub fn from_points<I: IntoIterator<Item = Point3>>(points: I) -> Aabb {
+        points.into_iter().fold(Aabb::empty(), |aabb, p| aabb.extend(p))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    /// the smallest box containing this one and `p`
+    pub fn extend(&self, p: Point3) -> Aabb {
+        Aabb {
+            min: Point3::new(self.min.x.min(p.x), self.min.y.min(p.y), self.min.z.min(p.z)),
+            max: Point3::new(self.max.x.max(p.x), self.max.y.max(p.y), self.max.z.max(p.z)),
+        }
+    }
+
+    /// the smallest box containing both boxes
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        self.extend(other.min).extend(other.max)
+    }
+
+    pub fn center(&self) -> Point3 {
+        self.min.midpoint(self.max)
+    }
+
+    /// half the size along each axis
+    pub fn half_extents(&self) -> Vector3 {
+        (self.max - self.min) / 2.0
+    }
+
+    pub fn contains(&self, p: Point3) -> bool {
+        p.x >= self.min.x && p.x <= self.max.x &&
+        p.y >= self.min.y && p.y <= self.max.y &&
+        p.z >= self.min.z && p.z <= self.max.z
+    }
+
+    /// The box around this box after transforming it by `m` (not the tightest box around the
+    /// transformed geometry, but never smaller than it).
+    pub fn transform(&self, m: &Matrix4) -> Aabb {
+        if self.is_empty() {
+            return *self;
+        }
+        // transform the center and add up how far each (rotated and scaled) half axis reaches along x, y and z
+        let center = m.transform_point(self.center());
+        let e = self.half_extents();
+        let extent = Vector3::new(
+            m.x.x.abs() * e.x + m.y.x.abs() * e.y + m.z.x.abs() * e.z,
+            m.x.y.abs() * e.x + m.y.y.abs() * e.y + m.z.y.abs() * e.z,
+            m.x.z.abs() * e.x + m.y.z.abs() * e.y + m.z.z.abs() * e.z);
+        Aabb { min: center - extent, max: center + extent }
+    }
+}
+
+/// The default is a sphere of radius 0 at the origin, also used for no points at all
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Point3,
+    pub radius: f32,
+}
+
+impl Default for BoundingSphere {
+    fn default() -> BoundingSphere {
+        BoundingSphere { center: Point3::origin(), radius: 0.0 }
+    }
+}
+
+impl BoundingSphere {
+    /// A sphere around the center of the points' bounding box, just large enough to contain them
+    /// all. Not the smallest possible sphere, but usually tighter than `from_aabb`.
+    pub fn from_points<I: IntoIterator<Item = Point3>>(points: I) -> BoundingSphere {
+        let points: Vec<Point3> = points.into_iter().collect();
+        let aabb = Aabb::from_points(points.iter().cloned());
+        if aabb.is_empty() {
+            return BoundingSphere::default();
+        }
+        let center = aabb.center();
+        let radius2 = points.iter().map(|&p| (p - center).magnitude2()).fold(0.0, f32::max);
+        BoundingSphere { center, radius: radius2.sqrt() }
+    }
+
+    /// the sphere through the corners of the box
+    pub fn from_aabb(aabb: &Aabb) -> BoundingSphere {
+        BoundingSphere { center: aabb.center(), radius: aabb.half_extents().magnitude() }
+    }
+
+    pub fn contains(&self, p: Point3) -> bool {
+        (p - self.center).magnitude2() <= self.radius * self.radius
+    }
+
+    /// The sphere after transforming it by `m`; with non-uniform scaling the radius grows by the
+    /// largest scale factor.
+    pub fn transform(&self, m: &Matrix4) -> BoundingSphere {
+        let scale = m.x.truncate().magnitude2().max(m.y.truncate().magnitude2()).max(m.z.truncate().magnitude2()).sqrt();
+        BoundingSphere { center: m.transform_point(self.center), radius: self.radius * scale }
+    }
+}
@@ -3,9 +3,11 @@
 #![allow(dead_code)]
 
 use cgmath;
-use cgmath::vec3;
+use cgmath::{vec3, ortho, perspective, Deg, Matrix3, Quaternion};
 use cgmath::prelude::*;
 
+use bookmarks::Bookmark;
+
 type Point3 = cgmath::Point3<f32>;
 type Vector3 = cgmath::Vector3<f32>;
 type Matrix4 = cgmath::Matrix4<f32>;
@@ -17,15 +19,120 @@ pub enum Camera_Movement {
     BACKWARD,
     LEFT,
     RIGHT,
+    UP,
+    DOWN,
+    // only supported by cameras that can roll (e.g. `QuatCamera`)
+    ROLL_LEFT,
+    ROLL_RIGHT,
 }
 use self::Camera_Movement::*;
 
+// What the scroll wheel does
+#[derive(PartialEq, Clone, Copy)]
+pub enum Camera_Scroll {
+    // change the field of view (the original behaviour)
+    SCROLL_ZOOM,
+    // change MovementSpeed
+    SCROLL_SPEED,
+}
+use self::Camera_Scroll::*;
+
+// Mouse buttons a camera can react to (e.g. dragging with `OrbitCamera`), again independent of the window system
+#[derive(PartialEq, Clone, Copy)]
+pub enum Camera_MouseButton {
+    MOUSE_LEFT,
+    MOUSE_MIDDLE,
+    MOUSE_RIGHT,
+}
+
 // Default camera values
 const YAW: f32 = -90.0;
 const PITCH: f32 = 0.0;
 const SPEED: f32 = 2.5;
 const SENSITIVTY: f32 = 0.1;
 const ZOOM: f32 = 45.0;
+const SCROLL_SPEED_FACTOR: f32 = 1.1;
+/// aspect ratio of all cameras until the first `SetAspect`; `common::processInput` sets the
+/// window's on the first frame
+pub const ASPECT: f32 = 4.0 / 3.0;
+const NEAR: f32 = 0.1;
+const FAR: f32 = 100.0;
+
+/// How a camera projects the scene. The vertical field of view of the perspective projections
+/// is the camera's `Zoom`, so the scroll wheel keeps working.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    Perspective { near: f32, far: f32 },
+    /// Perspective with the far plane at infinity and depth reversed (1 at the near plane, 0 at
+    /// infinity), for a more even depth precision. Needs depth cleared to 0 and `gl::DepthFunc(gl::GREATER)`;
+    /// for the full precision benefit also a floating point depth buffer and
+    /// `gl::ClipControl(gl::LOWER_LEFT, gl::ZERO_TO_ONE)` (GL 4.5).
+    InfiniteReverseZ { near: f32 },
+    /// `height` world units are visible vertically, the width follows from the aspect ratio
+    Orthographic { height: f32, near: f32, far: f32 },
+}
+
+impl Default for Projection {
+    fn default() -> Projection {
+        Projection::Perspective { near: NEAR, far: FAR }
+    }
+}
+
+impl Projection {
+    /// `fovy` in degrees, `aspect` is width / height
+    pub fn matrix(&self, fovy: f32, aspect: f32) -> Matrix4 {
+        match *self {
+            Projection::Perspective { near, far } => perspective(Deg(fovy), aspect, near, far),
+            Projection::InfiniteReverseZ { near } => {
+                let f = 1.0 / (fovy.to_radians() / 2.0).tan();
+                // column major: clip z = near and clip w = -eye z, so depth = near / distance
+                Matrix4::new(
+                    f / aspect, 0.0, 0.0, 0.0,
+                    0.0, f, 0.0, 0.0,
+                    0.0, 0.0, 0.0, -1.0,
+                    0.0, 0.0, near, 0.0)
+            }
+            Projection::Orthographic { height, near, far } => {
+                let (halfWidth, halfHeight) = (height * aspect / 2.0, height / 2.0);
+                ortho(-halfWidth, halfWidth, -halfHeight, halfHeight, near, far)
+            }
+        }
+    }
+}
+
+/// The interface shared by the camera types, so `process_events`/`processInput` in `common` and
+/// the tutorials work with any of them.
+pub trait CameraController {
+    fn GetViewMatrix(&self) -> Matrix4;
+    fn ProcessKeyboard(&mut self, direction: Camera_Movement, deltaTime: f32);
+    /// Analog movement, e.g. from a gamepad stick: like `ProcessKeyboard`, at `amount` (0..1) of the
+    /// full speed. By default this scales the time passed to `ProcessKeyboard`, which is the same
+    /// for cameras whose speed doesn't depend on how long a key was held.
+    fn ProcessMovement(&mut self, direction: Camera_Movement, amount: f32, deltaTime: f32) {
+        self.ProcessKeyboard(direction, deltaTime * amount)
+    }
+    fn ProcessMouseMovement(&mut self, xoffset: f32, yoffset: f32, constrainPitch: bool);
+    fn ProcessMouseScroll(&mut self, yoffset: f32);
+    fn GetProjectionMatrix(&self) -> Matrix4;
+    /// called by `processInput` every frame with the window's aspect ratio
+    fn SetAspect(&mut self, aspect: f32);
+    /// Press/release of a mouse button (the camera_button_* actions of `input`); only needed by
+    /// cameras that are controlled by dragging.
+    fn ProcessMouseButton(&mut self, _button: Camera_MouseButton, _pressed: bool) {}
+    /// Position and orientation (rotating camera space, looking down -Z with +Y up, into world
+    /// space) for recording flythroughs; `None` if the camera can't be placed freely.
+    fn GetPose(&self) -> Option<(Point3, Quaternion<f32>)> { None }
+    /// moves the camera to a pose returned by `GetPose`, e.g. during flythrough playback
+    fn SetPose(&mut self, _position: Point3, _orientation: Quaternion<f32>) {}
+    /// multiplies the movement speed, e.g. for sprinting; 1.0 is normal speed
+    fn SetSpeedModifier(&mut self, _modifier: f32) {}
+    /// Called once per frame after the `ProcessKeyboard` calls of that frame, for cameras that keep
+    /// moving on their own (e.g. `Camera` with `Acceleration`)
+    fn Update(&mut self, _deltaTime: f32) {}
+    /// the state to store in a camera bookmark (see `bookmarks`); `None` if the camera doesn't support them
+    fn GetBookmark(&self) -> Option<Bookmark> { None }
+    fn SetBookmark(&mut self, _bookmark: &Bookmark) {}
+}
 
 pub struct Camera {
     // Camera Attributes
@@ -41,6 +148,25 @@ pub struct Camera {
     pub MovementSpeed: f32,
     pub MouseSensitivity: f32,
     pub Zoom: f32,
+    /// MovementSpeed multiplier, set by `SetSpeedModifier`
+    pub SpeedModifier: f32,
+    /// UP/DOWN move along WorldUp if true, along the camera's Up vector otherwise
+    pub WorldUpMovement: bool,
+    pub ScrollMode: Camera_Scroll,
+    // Smooth movement
+    /// How quickly the camera speeds up towards MovementSpeed (1/s): after `1/Acceleration`
+    /// seconds it has reached ~63% of it. 0 moves at full speed immediately (the original behaviour).
+    pub Acceleration: f32,
+    /// How quickly the camera slows down when no key is held (1/s), like Acceleration.
+    /// 0 stops it as soon as the keys are released.
+    pub Damping: f32,
+    pub Velocity: Vector3,
+    /// direction requested by the ProcessKeyboard calls of the current frame, consumed by Update
+    pub MoveInput: Vector3,
+    // Projection
+    /// width / height of the viewport
+    pub Aspect: f32,
+    pub Projection: Projection,
 }
 
 impl Default for Camera {
@@ -56,6 +182,15 @@ impl Default for Camera {
             MovementSpeed: SPEED,
             MouseSensitivity: SENSITIVTY,
             Zoom: ZOOM,
+            SpeedModifier: 1.0,
+            WorldUpMovement: true,
+            ScrollMode: SCROLL_ZOOM,
+            Acceleration: 0.0,
+            Damping: 0.0,
+            Velocity: Vector3::zero(),
+            MoveInput: Vector3::zero(),
+            Aspect: ASPECT,
+            Projection: Projection::default(),
         };
         camera.updateCameraVectors();
         camera
@@ -68,21 +203,76 @@ impl Camera {
         Matrix4::look_at(self.Position, self.Position + self.Front, self.Up)
     }
 
+    /// Returns the projection matrix for the current field of view (`Zoom`) and aspect ratio
+    pub fn GetProjectionMatrix(&self) -> Matrix4 {
+        self.Projection.matrix(self.Zoom, self.Aspect)
+    }
+
+    /// Looks along the `front` direction of `orientation`; roll is lost, the camera stays upright.
+    pub fn SetPose(&mut self, position: Point3, orientation: Quaternion<f32>) {
+        let front = orientation * -Vector3::unit_z();
+        self.Position = position;
+        self.Yaw = front.z.atan2(front.x).to_degrees();
+        self.Pitch = front.y.max(-1.0).min(1.0).asin().to_degrees();
+        self.Velocity = Vector3::zero();
+        self.updateCameraVectors();
+    }
+
+    pub fn GetBookmark(&self) -> Bookmark {
+        Bookmark { position: self.Position, yaw: self.Yaw, pitch: self.Pitch, zoom: self.Zoom, speed: self.MovementSpeed }
+    }
+
+    /// Restores a bookmark; the camera stops if it was still moving (`Acceleration`).
+    pub fn SetBookmark(&mut self, bookmark: &Bookmark) {
+        self.Position = bookmark.position;
+        self.Yaw = bookmark.yaw;
+        self.Pitch = bookmark.pitch;
+        self.Zoom = bookmark.zoom;
+        self.MovementSpeed = bookmark.speed;
+        self.Velocity = Vector3::zero();
+        self.updateCameraVectors();
+    }
+
     /// Processes input received from any keyboard-like input system. Accepts input parameter in the form of camera defined ENUM (to abstract it from windowing systems)
+    /// With `Acceleration` > 0 this only collects the direction; `Update` moves the camera.
     pub fn ProcessKeyboard(&mut self, direction: Camera_Movement, deltaTime: f32) {
-        let velocity = self.MovementSpeed * deltaTime;
-        if direction == FORWARD {
-            self.Position += self.Front * velocity;
-        }
-        if direction == BACKWARD {
-            self.Position += -(self.Front * velocity);
-        }
-        if direction == LEFT {
-            self.Position += -(self.Right * velocity);
+        self.ProcessMovement(direction, 1.0, deltaTime)
+    }
+
+    /// Like `ProcessKeyboard`, at `amount` (0..1) of the full speed, also with smooth movement
+    pub fn ProcessMovement(&mut self, direction: Camera_Movement, amount: f32, deltaTime: f32) {
+        let up = if self.WorldUpMovement { self.WorldUp } else { self.Up };
+        let dir = match direction {
+            FORWARD => self.Front,
+            BACKWARD => -self.Front,
+            LEFT => -self.Right,
+            RIGHT => self.Right,
+            UP => up,
+            DOWN => -up,
+            // Euler angles relative to WorldUp can't express roll
+            ROLL_LEFT | ROLL_RIGHT => return,
+        };
+        if self.Acceleration > 0.0 {
+            self.MoveInput += dir * amount;
+        } else {
+            let velocity = self.MovementSpeed * self.SpeedModifier * amount * deltaTime;
+            self.Position += dir * velocity;
         }
-        if direction == RIGHT {
-            self.Position += self.Right * velocity;
+    }
+
+    /// Moves the camera with smooth movement (`Acceleration` > 0): the velocity approaches the
+    /// requested one exponentially, so the result doesn't depend on the frame rate.
+    pub fn Update(&mut self, deltaTime: f32) {
+        if self.Acceleration <= 0.0 {
+            return;
         }
+        // diagonal movement isn't faster
+        let input = if self.MoveInput.magnitude2() > 1.0 { self.MoveInput.normalize() } else { self.MoveInput };
+        let target = input * self.MovementSpeed * self.SpeedModifier;
+        let rate = if input.is_zero() { self.Damping } else { self.Acceleration };
+        self.Velocity = if rate > 0.0 { target + (self.Velocity - target) * (-rate * deltaTime).exp() } else { target };
+        self.Position += self.Velocity * deltaTime;
+        self.MoveInput = Vector3::zero();
     }
 
     /// Processes input received from a mouse input system. Expects the offset value in both the x and y direction.
@@ -109,6 +299,10 @@ impl Camera {
 
     // Processes input received from a mouse scroll-wheel event. Only requires input on the vertical wheel-axis
     pub fn ProcessMouseScroll(&mut self, yoffset: f32) {
+        if self.ScrollMode == SCROLL_SPEED {
+            self.MovementSpeed *= SCROLL_SPEED_FACTOR.powf(yoffset);
+            return;
+        }
         if self.Zoom >= 1.0 && self.Zoom <= 45.0 {
             self.Zoom -= yoffset;
         }
@@ -134,3 +328,59 @@ impl Camera {
         self.Up = self.Right.cross(self.Front).normalize();
     }
 }
+
+impl CameraController for Camera {
+    fn GetViewMatrix(&self) -> Matrix4 {
+        Camera::GetViewMatrix(self)
+    }
+
+    fn ProcessKeyboard(&mut self, direction: Camera_Movement, deltaTime: f32) {
+        Camera::ProcessKeyboard(self, direction, deltaTime)
+    }
+
+    fn ProcessMovement(&mut self, direction: Camera_Movement, amount: f32, deltaTime: f32) {
+        Camera::ProcessMovement(self, direction, amount, deltaTime)
+    }
+
+    fn ProcessMouseMovement(&mut self, xoffset: f32, yoffset: f32, constrainPitch: bool) {
+        Camera::ProcessMouseMovement(self, xoffset, yoffset, constrainPitch)
+    }
+
+    fn ProcessMouseScroll(&mut self, yoffset: f32) {
+        Camera::ProcessMouseScroll(self, yoffset)
+    }
+
+    fn GetProjectionMatrix(&self) -> Matrix4 {
+        Camera::GetProjectionMatrix(self)
+    }
+
+    fn SetAspect(&mut self, aspect: f32) {
+        self.Aspect = aspect;
+    }
+
+    fn GetPose(&self) -> Option<(Point3, Quaternion<f32>)> {
+        // the columns are the world space directions of the camera's x, y and z axes
+        let rotation = Matrix3::from_cols(self.Right, self.Up, -self.Front);
+        Some((self.Position, Quaternion::from(rotation).normalize()))
+    }
+
+    fn SetPose(&mut self, position: Point3, orientation: Quaternion<f32>) {
+        Camera::SetPose(self, position, orientation)
+    }
+
+    fn SetSpeedModifier(&mut self, modifier: f32) {
+        self.SpeedModifier = modifier;
+    }
+
+    fn Update(&mut self, deltaTime: f32) {
+        Camera::Update(self, deltaTime)
+    }
+
+    fn GetBookmark(&self) -> Option<Bookmark> {
+        Some(Camera::GetBookmark(self))
+    }
+
+    fn SetBookmark(&mut self, bookmark: &Bookmark) {
+        Camera::SetBookmark(self, bookmark)
+    }
+}
@@ -2,28 +2,33 @@
 #![allow(dead_code)]
 /// Common code that the original tutorials repeat over and over and over and over
 
+use std::cell::RefCell;
 use std::os::raw::c_void;
 use std::path::Path;
 use std::sync::mpsc::Receiver;
 
 use gl;
 extern crate glfw;
-use self::glfw::{Key, Action};
 
 use image;
 use image::GenericImage;
 use image::DynamicImage::*;
 
-use camera::Camera;
+use bookmarks::{self, Bookmarks};
+use camera::CameraController;
+use flythrough::Flythrough;
+use gamepad::GamepadConfig;
+use input;
 use camera::Camera_Movement::*;
+use camera::Camera_MouseButton::*;
 
 /// Event processing function as introduced in 1.7.4 (Camera Class) and used in
-/// most later tutorials
-pub fn process_events(events: &Receiver<(f64, glfw::WindowEvent)>,
+/// most later tutorials. Works with any camera type (`Camera`, `QuatCamera`, `OrbitCamera`).
+pub fn process_events<C: CameraController>(events: &Receiver<(f64, glfw::WindowEvent)>,
                   firstMouse: &mut bool,
                   lastX: &mut f32,
                   lastY: &mut f32,
-                  camera: &mut Camera) {
+                  camera: &mut C) {
     for (_, event) in glfw::flush_messages(events) {
         match event {
             glfw::WindowEvent::FramebufferSize(width, height) => {
@@ -48,34 +53,201 @@ pub fn process_events(events: &Receiver<(f64, glfw::WindowEvent)>,
                 camera.ProcessMouseMovement(xoffset, yoffset, true);
             }
             glfw::WindowEvent::Scroll(_xoffset, yoffset) => {
-                camera.ProcessMouseScroll(yoffset as f32);
+                // applied by `processInput`, as the zoom_in/zoom_out actions
+                input::scroll_event(yoffset as f32);
             }
             _ => {}
         }
     }
 }
 
+const SPRINT_MODIFIER: f32 = 3.0;
+const FLYTHROUGH_FILE: &str = "flythrough.txt";
+
+const JOYSTICKS: [glfw::JoystickId; 4] = [
+    glfw::JoystickId::Joystick1, glfw::JoystickId::Joystick2, glfw::JoystickId::Joystick3, glfw::JoystickId::Joystick4,
+];
+
+thread_local! {
+    static FLYTHROUGH: RefCell<Flythrough> = RefCell::new(Flythrough::default());
+    static GAMEPAD: RefCell<GamepadConfig> = RefCell::new(GamepadConfig::default());
+    static BOOKMARKS: RefCell<Bookmarks> = RefCell::new(loadBookmarks());
+    /// bookmark to restore in the first `processInput` call
+    static START_BOOKMARK: RefCell<Option<u32>> = RefCell::new(None);
+}
+
+fn loadBookmarks() -> Bookmarks {
+    if !Path::new(bookmarks::BOOKMARK_FILE).exists() {
+        return Bookmarks::default();
+    }
+    Bookmarks::Load(bookmarks::BOOKMARK_FILE).unwrap_or_else(|err| {
+        println!("ERROR::BOOKMARKS: failed to load {}: {}", bookmarks::BOOKMARK_FILE, err);
+        Bookmarks::default()
+    })
+}
+
+/// makes the first `processInput` call move the camera to a bookmark (`--bookmark N` on the command line)
+pub fn setStartBookmark(slot: u32) {
+    START_BOOKMARK.with(|start| *start.borrow_mut() = Some(slot));
+}
+
+/// replaces the axis mapping `processInput` uses for joysticks/gamepads
+pub fn setGamepadConfig(config: GamepadConfig) {
+    GAMEPAD.with(|gamepad| *gamepad.borrow_mut() = config);
+}
+
 /// Input processing function as introduced in 1.7.4 (Camera Class) and used in
-/// most later tutorials
-pub fn processInput(window: &mut glfw::Window, deltaTime: f32, camera: &mut Camera) {
-    if window.get_key(Key::Escape) == Action::Press {
+/// most later tutorials. The keys are configurable (see `input`); by default:
+/// WASD move, R/F move up/down, Q/E roll cameras that support it (`QuatCamera`), Left Shift moves
+/// 3x faster and the scroll wheel zooms. 1-9 restore camera bookmarks and Left Control + 1-9
+/// stores them (see `bookmarks`). F5-F8 record and play back a camera path (see
+/// `flythrough`); while it plays, the keys don't move the camera.
+/// The first connected joystick/gamepad moves the camera as well (see `gamepad`).
+/// Updates the `input` state, so tutorials can query their own actions after calling this.
+pub fn processInput<C: CameraController>(window: &mut glfw::Window, deltaTime: f32, camera: &mut C) {
+    input::update(window);
+
+    // the camera follows the window's aspect ratio; a minimized window has a size of 0
+    let (width, height) = window.get_framebuffer_size();
+    if width > 0 && height > 0 {
+        camera.SetAspect(width as f32 / height as f32);
+    }
+
+    if input::held("quit") {
         window.set_should_close(true)
     }
 
-    if window.get_key(Key::W) == Action::Press {
-        camera.ProcessKeyboard(FORWARD, deltaTime);
+    processBookmarks(camera);
+    if processFlythrough(deltaTime, camera) {
+        return;
+    }
+
+    camera.SetSpeedModifier(if input::held("sprint") { SPRINT_MODIFIER } else { 1.0 });
+    let movements = [
+        ("forward", FORWARD), ("backward", BACKWARD), ("left", LEFT), ("right", RIGHT),
+        ("up", UP), ("down", DOWN), ("roll_left", ROLL_LEFT), ("roll_right", ROLL_RIGHT),
+    ];
+    for &(action, movement) in &movements {
+        if input::held(action) {
+            camera.ProcessKeyboard(movement, deltaTime);
+        }
+    }
+    processGamepad(window, deltaTime, camera);
+    camera.Update(deltaTime);
+
+    let zoom = input::steps("zoom_in") - input::steps("zoom_out");
+    if zoom != 0.0 {
+        camera.ProcessMouseScroll(zoom);
+    }
+
+    let buttons = [
+        ("camera_button_left", MOUSE_LEFT), ("camera_button_middle", MOUSE_MIDDLE), ("camera_button_right", MOUSE_RIGHT),
+    ];
+    for &(action, button) in &buttons {
+        if input::pressed(action) {
+            camera.ProcessMouseButton(button, true);
+        } else if input::released(action) {
+            camera.ProcessMouseButton(button, false);
+        }
+    }
+}
+
+/// moves the camera with the first connected joystick, at the speed given by the stick and trigger amounts
+fn processGamepad<C: CameraController>(window: &glfw::Window, deltaTime: f32, camera: &mut C) {
+    let joystick = match JOYSTICKS.iter().map(|&id| window.glfw.get_joystick(id)).find(|joystick| joystick.is_present()) {
+        Some(joystick) => joystick,
+        None => return,
+    };
+    let input = GAMEPAD.with(|gamepad| gamepad.borrow().map(&joystick.get_axes(), deltaTime));
+
+    let axes = [(input.forward, FORWARD, BACKWARD), (input.right, RIGHT, LEFT), (input.up, UP, DOWN)];
+    for &(amount, positive, negative) in &axes {
+        if amount != 0.0 {
+            camera.ProcessMovement(if amount > 0.0 { positive } else { negative }, amount.abs(), deltaTime);
+        }
     }
-    if window.get_key(Key::S) == Action::Press {
-        camera.ProcessKeyboard(BACKWARD, deltaTime);
+    if input.look_x != 0.0 || input.look_y != 0.0 {
+        camera.ProcessMouseMovement(input.look_x, input.look_y, true);
     }
-    if window.get_key(Key::A) == Action::Press {
-        camera.ProcessKeyboard(LEFT, deltaTime);
+}
+
+/// handles the bookmark actions and the bookmark requested at startup
+fn processBookmarks<C: CameraController>(camera: &mut C) {
+    let mut restore = START_BOOKMARK.with(|start| start.borrow_mut().take());
+    for slot in 1..=bookmarks::SLOTS {
+        if !input::pressed(&format!("bookmark_{}", slot)) {
+            continue;
+        }
+        if !input::held("bookmark_save") {
+            restore = Some(slot);
+            continue;
+        }
+        let bookmark = match camera.GetBookmark() {
+            Some(bookmark) => bookmark,
+            None => {
+                println!("bookmarks: this camera doesn't support bookmarks");
+                continue;
+            }
+        };
+        BOOKMARKS.with(|bookmarks| {
+            let bookmarks = &mut *bookmarks.borrow_mut();
+            bookmarks.Set(slot, bookmark);
+            match bookmarks.Save(bookmarks::BOOKMARK_FILE) {
+                Ok(()) => println!("bookmarks: saved bookmark {} to {}", slot, bookmarks::BOOKMARK_FILE),
+                Err(err) => println!("bookmarks: failed to save {}: {}", bookmarks::BOOKMARK_FILE, err),
+            }
+        });
     }
-    if window.get_key(Key::D) == Action::Press {
-        camera.ProcessKeyboard(RIGHT, deltaTime);
+
+    if let Some(slot) = restore {
+        match BOOKMARKS.with(|bookmarks| bookmarks.borrow().Get(slot)) {
+            Some(bookmark) => camera.SetBookmark(&bookmark),
+            None => println!("bookmarks: no bookmark {} in {}", slot, bookmarks::BOOKMARK_FILE),
+        }
     }
 }
 
+/// handles the flythrough actions and advances playback; true while a flythrough is playing
+fn processFlythrough<C: CameraController>(deltaTime: f32, camera: &mut C) -> bool {
+    FLYTHROUGH.with(|flythrough| {
+        let flythrough = &mut *flythrough.borrow_mut();
+        if input::pressed("flythrough_record") {
+            if flythrough.Record(camera) {
+                println!("flythrough: recorded keyframe {}", flythrough.keyframes.len());
+            } else {
+                println!("flythrough: this camera doesn't support recording");
+            }
+        }
+        if input::pressed("flythrough_play") {
+            if flythrough.IsPlaying() {
+                flythrough.Stop();
+            } else if flythrough.keyframes.len() < 2 {
+                println!("flythrough: record at least 2 keyframes first");
+            } else {
+                flythrough.Play();
+            }
+        }
+        if input::pressed("flythrough_save") {
+            match flythrough.Save(FLYTHROUGH_FILE) {
+                Ok(()) => println!("flythrough: saved {} keyframes to {}", flythrough.keyframes.len(), FLYTHROUGH_FILE),
+                Err(err) => println!("flythrough: failed to save {}: {}", FLYTHROUGH_FILE, err),
+            }
+        }
+        if input::pressed("flythrough_load") {
+            match Flythrough::Load(FLYTHROUGH_FILE, flythrough.duration) {
+                Ok(loaded) => {
+                    *flythrough = loaded;
+                    println!("flythrough: loaded {} keyframes from {}", flythrough.keyframes.len(), FLYTHROUGH_FILE);
+                }
+                Err(err) => println!("flythrough: failed to load {}: {}", FLYTHROUGH_FILE, err),
+            }
+        }
+
+        flythrough.Update(deltaTime, camera);
+        flythrough.IsPlaying()
+    })
+}
+
 /// utility function for loading a 2D texture from file
 /// ---------------------------------------------------
 #[allow(dead_code)]
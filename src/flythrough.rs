@@ -0,0 +1,172 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// Recorded camera paths for repeatable demos and performance comparisons: keyframes of camera
+/// position and orientation, played back over a fixed duration with a Catmull-Rom spline through
+/// the positions and slerp between the orientations.
+///
+/// `common::processInput` wires it up for every tutorial with a camera that supports it (`Camera`,
+/// `QuatCamera`): F5 records a keyframe, F6 starts/stops playback, F7 saves the path to
+/// `flythrough.txt` and F8 loads it.
+///
+/// The file has one keyframe per line: position x y z, then orientation quaternion w x y z.
+/// Empty lines and lines starting with `#` are ignored.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use cgmath;
+use cgmath::Quaternion;
+use cgmath::prelude::*;
+
+use camera::CameraController;
+
+type Point3 = cgmath::Point3<f32>;
+type Vector3 = cgmath::Vector3<f32>;
+
+const DURATION: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe {
+    pub position: Point3,
+    /// rotates camera space (looking down -Z with +Y up) into world space, like `QuatCamera::Orientation`
+    pub orientation: Quaternion<f32>,
+}
+
+pub struct Flythrough {
+    pub keyframes: Vec<Keyframe>,
+    /// seconds for the whole path, regardless of the number of keyframes
+    pub duration: f32,
+    /// playback position in seconds, `None` while not playing
+    time: Option<f32>,
+}
+
+impl Default for Flythrough {
+    fn default() -> Flythrough {
+        Flythrough { keyframes: Vec::new(), duration: DURATION, time: None }
+    }
+}
+
+impl Flythrough {
+    pub fn new(duration: f32) -> Flythrough {
+        Flythrough { duration, ..Flythrough::default() }
+    }
+
+    /// adds the current pose of the camera as the last keyframe; false if the camera doesn't support it
+    pub fn Record<C: CameraController>(&mut self, camera: &C) -> bool {
+        match camera.GetPose() {
+            Some((position, orientation)) => {
+                self.keyframes.push(Keyframe { position, orientation });
+                true
+            }
+            None => false
+        }
+    }
+
+    /// starts playback from the beginning; needs at least two keyframes
+    pub fn Play(&mut self) {
+        if self.keyframes.len() >= 2 {
+            self.time = Some(0.0);
+        }
+    }
+
+    pub fn Stop(&mut self) {
+        self.time = None;
+    }
+
+    pub fn IsPlaying(&self) -> bool {
+        self.time.is_some()
+    }
+
+    /// Advances playback and moves the camera to the new pose. Stops at the end of the path.
+    pub fn Update<C: CameraController>(&mut self, deltaTime: f32, camera: &mut C) {
+        if let Some(time) = self.time {
+            let time = (time + deltaTime).min(self.duration);
+            match self.Sample(time / self.duration) {
+                Some(keyframe) => camera.SetPose(keyframe.position, keyframe.orientation),
+                // the keyframes were removed during playback
+                None => {
+                    self.time = None;
+                    return;
+                }
+            }
+            self.time = if time < self.duration { Some(time) } else { None };
+        }
+    }
+
+    /// The interpolated pose at `t` (0 = first keyframe, 1 = last keyframe); every segment between
+    /// two keyframes takes the same time. `None` without keyframes.
+    pub fn Sample(&self, t: f32) -> Option<Keyframe> {
+        let k = &self.keyframes;
+        if k.is_empty() {
+            return None;
+        }
+        let last = k.len() - 1;
+        // exactly the end points, without rounding errors of the interpolation
+        if t <= 0.0 {
+            return Some(k[0]);
+        } else if t >= 1.0 {
+            return Some(k[last]);
+        }
+        let t = t.max(0.0).min(1.0) * last as f32;
+        let i = (t.floor() as usize).min(last.saturating_sub(1));
+        let (i1, i2) = (i, (i + 1).min(last));
+        let local = t - i as f32;
+
+        // the end points are repeated to get tangents for the first and last segment
+        let (p0, p1, p2, p3) = (k[i1.saturating_sub(1)].position, k[i1].position, k[i2].position, k[(i2 + 1).min(last)].position);
+        let position = catmullRom(p0.to_vec(), p1.to_vec(), p2.to_vec(), p3.to_vec(), local);
+
+        let (q1, mut q2) = (k[i1].orientation, k[i2].orientation);
+        // q and -q are the same rotation; take the shorter way round
+        if q1.dot(q2) < 0.0 {
+            q2 = -q2;
+        }
+        Some(Keyframe { position: Point3::from_vec(position), orientation: q1.slerp(q2, local).normalize() })
+    }
+
+    pub fn Save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        writeln!(file, "# position x y z, orientation w x y z")?;
+        for k in &self.keyframes {
+            let (p, q) = (k.position, k.orientation);
+            writeln!(file, "{} {} {} {} {} {} {}", p.x, p.y, p.z, q.s, q.v.x, q.v.y, q.v.z)?;
+        }
+        Ok(())
+    }
+
+    /// loads the keyframes of a file written by `Save`, playing back in `duration` seconds
+    pub fn Load<P: AsRef<Path>>(path: P, duration: f32) -> io::Result<Flythrough> {
+        let mut flythrough = Flythrough::new(duration);
+        for (lineNr, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let values: Vec<f32> = line.split_whitespace().map(|v| v.parse::<f32>()).collect::<Result<_, _>>()
+                .map_err(|err| invalidData(lineNr, &err.to_string()))?;
+            if values.len() != 7 {
+                return Err(invalidData(lineNr, &format!("expected 7 numbers, found {}", values.len())));
+            }
+            flythrough.keyframes.push(Keyframe {
+                position: Point3::new(values[0], values[1], values[2]),
+                orientation: Quaternion::new(values[3], values[4], values[5], values[6]).normalize(),
+            });
+        }
+        Ok(flythrough)
+    }
+}
+
+/// uniform Catmull-Rom spline through p1 (t = 0) and p2 (t = 1)
+fn catmullRom(p0: Vector3, p1: Vector3, p2: Vector3, p3: Vector3, t: f32) -> Vector3 {
+    let (t2, t3) = (t * t, t * t * t);
+    (p1 * 2.0
+        + (p2 - p0) * t
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3) * 0.5
+}
+
+fn invalidData(lineNr: usize, message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", lineNr + 1, message))
+}
@@ -0,0 +1,122 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// View-frustum culling: the six planes of a (view-)projection matrix and conservative tests of
+/// points, spheres and boxes against them. Usage:
+///
+///     let frustum = Frustum::from_matrix(&(projection * view));
+///     if frustum.intersects_sphere(&sphere) { /* draw */ }
+
+use std::fmt;
+
+use cgmath;
+use cgmath::prelude::*;
+
+use bounds::{Aabb, BoundingSphere};
+
+type Point3 = cgmath::Point3<f32>;
+type Vector3 = cgmath::Vector3<f32>;
+type Vector4 = cgmath::Vector4<f32>;
+type Matrix4 = cgmath::Matrix4<f32>;
+
+/// Points `p` with `normal.dot(p) + d >= 0` are on the inside
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: Vector3,
+    pub d: f32,
+}
+
+impl Plane {
+    /// from the coefficients (a, b, c, d) of `ax + by + cz + d = 0`, normalized so `distance` is
+    /// in world units
+    fn from_coefficients(v: Vector4) -> Plane {
+        let length = v.truncate().magnitude();
+        if length < 1e-6 {
+            // e.g. the far plane of an infinite projection: everything is inside
+            return Plane { normal: Vector3::zero(), d: 0.0 };
+        }
+        Plane { normal: v.truncate() / length, d: v.w / length }
+    }
+
+    /// signed distance, positive on the inside
+    pub fn distance(&self, p: Point3) -> f32 {
+        self.normal.dot(p.to_vec()) + self.d
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    /// left, right, bottom, top, near, far
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// Extracts the planes from a matrix that transforms into clip space (Gribb/Hartmann). With
+    /// `projection * view` the planes are in world space, with `projection * view * model` in the
+    /// model's local space.
+    pub fn from_matrix(m: &Matrix4) -> Frustum {
+        let (r1, r2, r3, r4) = (m.row(0), m.row(1), m.row(2), m.row(3));
+        Frustum {
+            planes: [
+                Plane::from_coefficients(r4 + r1),
+                Plane::from_coefficients(r4 - r1),
+                Plane::from_coefficients(r4 + r2),
+                Plane::from_coefficients(r4 - r2),
+                Plane::from_coefficients(r4 + r3),
+                Plane::from_coefficients(r4 - r3),
+            ]
+        }
+    }
+
+    pub fn contains_point(&self, p: Point3) -> bool {
+        self.planes.iter().all(|plane| plane.distance(p) >= 0.0)
+    }
+
+    /// false if the sphere is completely outside; may be true for some spheres that are just
+    /// outside a corner of the frustum
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes.iter().all(|plane| plane.distance(sphere.center) >= -sphere.radius)
+    }
+
+    /// false if the box is completely outside (same caveat as `intersects_sphere`)
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        let (center, e) = (aabb.center(), aabb.half_extents());
+        self.planes.iter().all(|plane| {
+            // how far the box reaches towards the inside of the plane
+            let n = plane.normal;
+            let reach = n.x.abs() * e.x + n.y.abs() * e.y + n.z.abs() * e.z;
+            plane.distance(center) >= -reach
+        })
+    }
+}
+
+/// Counts tested and culled objects, e.g. per frame
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CullStats {
+    pub tested: usize,
+    pub culled: usize,
+}
+
+impl CullStats {
+    pub fn visible(&self) -> usize {
+        self.tested - self.culled
+    }
+
+    pub fn reset(&mut self) {
+        *self = CullStats::default();
+    }
+
+    /// counts one test and passes its result through
+    pub fn record(&mut self, visible: bool) -> bool {
+        self.tested += 1;
+        if !visible {
+            self.culled += 1;
+        }
+        visible
+    }
+}
+
+impl fmt::Display for CullStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "culled {} of {}", self.culled, self.tested)
+    }
+}
@@ -0,0 +1,138 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// Maps raw joystick axes (as reported by GLFW's joystick API, -1..1) to camera movement and
+/// look input, with dead zones and response curves. This part needs no window or device;
+/// `common::processInput` polls the first connected joystick and feeds the result into
+/// `ProcessMovement`/`ProcessMouseMovement`.
+///
+/// The default axis layout is the one GLFW reports for XInput (Xbox) controllers: left stick
+/// 0/1, right stick 2/3, triggers 4/5. Other controllers and platforms differ, so the axis
+/// indices and inversions are part of `GamepadConfig`.
+
+/// How one axis value is turned into an input amount
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisCurve {
+    /// values closer to the rest position than this are ignored (controllers rarely rest at exactly 0)
+    pub dead_zone: f32,
+    /// the response curve after the dead zone: 1 is linear, larger values give finer control near
+    /// the center while still reaching full speed
+    pub exponent: f32,
+    /// multiplies the result
+    pub sensitivity: f32,
+}
+
+impl Default for AxisCurve {
+    fn default() -> AxisCurve {
+        AxisCurve { dead_zone: 0.15, exponent: 2.0, sensitivity: 1.0 }
+    }
+}
+
+impl AxisCurve {
+    /// maps `value` (-1..1) so the dead zone gives 0 and the rest of the range still covers 0..1
+    pub fn apply(&self, value: f32) -> f32 {
+        self.magnitude(value.abs()) * value.signum()
+    }
+
+    /// Maps a stick position with a radial dead zone: unlike applying `apply` to both axes, this
+    /// doesn't snap diagonal movement to the axes.
+    pub fn apply_stick(&self, x: f32, y: f32) -> (f32, f32) {
+        let length = (x * x + y * y).sqrt();
+        if length <= self.dead_zone {
+            return (0.0, 0.0);
+        }
+        let scale = self.magnitude(length) / length;
+        (x * scale, y * scale)
+    }
+
+    fn magnitude(&self, value: f32) -> f32 {
+        if value <= self.dead_zone {
+            return 0.0;
+        }
+        let normalized = ((value - self.dead_zone) / (1.0 - self.dead_zone)).min(1.0);
+        normalized.powf(self.exponent) * self.sensitivity
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GamepadConfig {
+    // axis indices; `None` if not used
+    pub move_x: Option<usize>,
+    pub move_y: Option<usize>,
+    pub look_x: Option<usize>,
+    pub look_y: Option<usize>,
+    pub trigger_up: Option<usize>,
+    pub trigger_down: Option<usize>,
+    /// flip the sign of the y axes, so pushing a stick forward gives a positive value
+    pub invert_move_y: bool,
+    pub invert_look_y: bool,
+    /// triggers that rest at -1 instead of 0 (the usual case with GLFW)
+    pub triggers_rest_at_minus_one: bool,
+    pub move_curve: AxisCurve,
+    pub look_curve: AxisCurve,
+    pub trigger_curve: AxisCurve,
+    /// mouse movement in pixels per second at full deflection, scaled by the camera's MouseSensitivity as usual
+    pub look_speed: f32,
+}
+
+impl Default for GamepadConfig {
+    fn default() -> GamepadConfig {
+        GamepadConfig {
+            move_x: Some(0),
+            move_y: Some(1),
+            look_x: Some(2),
+            look_y: Some(3),
+            trigger_up: Some(5),
+            trigger_down: Some(4),
+            invert_move_y: true,
+            invert_look_y: true,
+            triggers_rest_at_minus_one: true,
+            move_curve: AxisCurve::default(),
+            look_curve: AxisCurve::default(),
+            trigger_curve: AxisCurve { dead_zone: 0.05, exponent: 1.0, sensitivity: 1.0 },
+            look_speed: 1000.0,
+        }
+    }
+}
+
+/// The input of one frame. Movement amounts are -1..1 (times the curve's sensitivity), the look
+/// amounts are already in "pixels" for `ProcessMouseMovement`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GamepadInput {
+    /// positive is right
+    pub right: f32,
+    /// positive is forward
+    pub forward: f32,
+    /// positive is up
+    pub up: f32,
+    pub look_x: f32,
+    /// positive is looking up, like the (reversed) mouse y offset in `process_events`
+    pub look_y: f32,
+}
+
+impl GamepadConfig {
+    /// Maps raw axis values to movement and look input for a frame of `deltaTime` seconds.
+    /// Axes that the joystick doesn't have count as centered.
+    pub fn map(&self, axes: &[f32], deltaTime: f32) -> GamepadInput {
+        let axis = |index: Option<usize>| index.and_then(|i| axes.get(i).cloned()).unwrap_or(0.0);
+        let flip = |invert: bool| if invert { -1.0 } else { 1.0 };
+        let trigger = |index: Option<usize>| {
+            let value = axis(index);
+            let value = if self.triggers_rest_at_minus_one && index.map_or(false, |i| i < axes.len()) {
+                (value + 1.0) / 2.0
+            } else {
+                value
+            };
+            self.trigger_curve.apply(value.max(0.0))
+        };
+
+        let (right, forward) = self.move_curve.apply_stick(axis(self.move_x), axis(self.move_y) * flip(self.invert_move_y));
+        let (lookX, lookY) = self.look_curve.apply_stick(axis(self.look_x), axis(self.look_y) * flip(self.invert_look_y));
+        GamepadInput {
+            right,
+            forward,
+            up: trigger(self.trigger_up) - trigger(self.trigger_down),
+            look_x: lookX * self.look_speed * deltaTime,
+            look_y: lookY * self.look_speed * deltaTime,
+        }
+    }
+}
@@ -0,0 +1,358 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// Named input actions instead of hard-coded keys, so keyboard layouts other than QWERTY (and
+/// personal preferences) only need a config file. `common::processInput` calls `update` once per
+/// frame; after that, the camera code and the tutorials query actions by name:
+///
+///     if input::pressed("toggle_blinn") { blinn = !blinn; }
+///
+/// The bindings are the defaults below, overridden per action by `input.cfg` in the working
+/// directory if it exists. Its lines look like this (`#` starts a comment, names are case-insensitive):
+///
+///     forward = Z, Up
+///     toggle_blinn = MouseRight
+///     zoom_in = ScrollUp, KpAdd
+///
+/// Key names are the `glfw::Key` variants (`A`, `Num1`, `F5`, `Space`, `LeftShift`, `Kp0`, ...),
+/// mouse buttons are `MouseLeft`, `MouseMiddle`, `MouseRight` and `Mouse4`-`Mouse8`, the scroll
+/// wheel is `ScrollUp`/`ScrollDown`.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::mem;
+use std::path::Path;
+
+extern crate glfw;
+use self::glfw::{Action, Key, MouseButton};
+
+pub const CONFIG_FILE: &str = "input.cfg";
+
+const DEFAULT_BINDINGS: &[(&str, &str)] = &[
+    ("quit", "Escape"),
+    // camera movement
+    ("forward", "W"),
+    ("backward", "S"),
+    ("left", "A"),
+    ("right", "D"),
+    ("up", "R"),
+    ("down", "F"),
+    ("roll_left", "Q"),
+    ("roll_right", "E"),
+    ("sprint", "LeftShift"),
+    ("zoom_in", "ScrollUp"),
+    ("zoom_out", "ScrollDown"),
+    // passed on as `Camera_MouseButton`s, e.g. for dragging `OrbitCamera`
+    ("camera_button_left", "MouseLeft"),
+    ("camera_button_middle", "MouseMiddle"),
+    ("camera_button_right", "MouseRight"),
+    // see `flythrough`
+    ("flythrough_record", "F5"),
+    ("flythrough_play", "F6"),
+    ("flythrough_save", "F7"),
+    ("flythrough_load", "F8"),
+    // see `bookmarks`: bookmark_N jumps to a bookmark, or stores one while bookmark_save is held
+    ("bookmark_save", "LeftControl"),
+    ("bookmark_1", "Num1"),
+    ("bookmark_2", "Num2"),
+    ("bookmark_3", "Num3"),
+    ("bookmark_4", "Num4"),
+    ("bookmark_5", "Num5"),
+    ("bookmark_6", "Num6"),
+    ("bookmark_7", "Num7"),
+    ("bookmark_8", "Num8"),
+    ("bookmark_9", "Num9"),
+    // tutorial specific; each is only queried by one tutorial, so they may share keys with each
+    // other and with camera actions that tutorial's camera ignores (`Camera` doesn't roll)
+    ("toggle_blinn", "B"),
+    ("toggle_gamma", "Space"),
+    ("toggle_hdr", "Space"),
+    ("exposure_down", "Q"),
+    ("exposure_up", "E"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Binding {
+    Key(Key),
+    MouseButton(MouseButton),
+    ScrollUp,
+    ScrollDown,
+}
+
+macro_rules! keys {
+    ($($name:ident),*) => { &[$((stringify!($name), Key::$name)),*] }
+}
+
+const KEYS: &[(&str, Key)] = keys!(
+    Space, Apostrophe, Comma, Minus, Period, Slash,
+    Num0, Num1, Num2, Num3, Num4, Num5, Num6, Num7, Num8, Num9,
+    Semicolon, Equal,
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    LeftBracket, Backslash, RightBracket, GraveAccent, World1, World2,
+    Escape, Enter, Tab, Backspace, Insert, Delete, Right, Left, Down, Up, PageUp, PageDown, Home, End,
+    CapsLock, ScrollLock, NumLock, PrintScreen, Pause,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15, F16, F17, F18, F19, F20, F21, F22, F23, F24, F25,
+    Kp0, Kp1, Kp2, Kp3, Kp4, Kp5, Kp6, Kp7, Kp8, Kp9, KpDecimal, KpDivide, KpMultiply, KpSubtract, KpAdd, KpEnter, KpEqual,
+    LeftShift, LeftControl, LeftAlt, LeftSuper, RightShift, RightControl, RightAlt, RightSuper, Menu);
+
+const MOUSE_BUTTONS: &[(&str, MouseButton)] = &[
+    ("MouseLeft", glfw::MouseButtonLeft),
+    ("MouseRight", glfw::MouseButtonRight),
+    ("MouseMiddle", glfw::MouseButtonMiddle),
+    ("Mouse4", MouseButton::Button4),
+    ("Mouse5", MouseButton::Button5),
+    ("Mouse6", MouseButton::Button6),
+    ("Mouse7", MouseButton::Button7),
+    ("Mouse8", MouseButton::Button8),
+];
+
+impl Binding {
+    pub fn parse(name: &str) -> Option<Binding> {
+        let name = name.trim();
+        let matches = |candidate: &&str| candidate.eq_ignore_ascii_case(name);
+        if matches(&"ScrollUp") {
+            Some(Binding::ScrollUp)
+        } else if matches(&"ScrollDown") {
+            Some(Binding::ScrollDown)
+        } else if let Some(&(_, button)) = MOUSE_BUTTONS.iter().find(|b| matches(&b.0)) {
+            Some(Binding::MouseButton(button))
+        } else {
+            KEYS.iter().find(|k| matches(&k.0)).map(|&(_, key)| Binding::Key(key))
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    /// the name `parse` accepts
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Binding::ScrollUp => Some("ScrollUp"),
+            Binding::ScrollDown => Some("ScrollDown"),
+            Binding::MouseButton(button) => MOUSE_BUTTONS.iter().find(|b| b.1 == button).map(|b| b.0),
+            Binding::Key(key) => KEYS.iter().find(|k| k.1 == key).map(|k| k.0),
+        };
+        match name {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "{:?}", self),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum InputConfigError {
+    Io(io::Error),
+    /// `line` is 1-based
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for InputConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            InputConfigError::Io(ref err) => write!(f, "{}", err),
+            InputConfigError::Parse { line, ref message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for InputConfigError {}
+
+impl From<io::Error> for InputConfigError {
+    fn from(err: io::Error) -> InputConfigError {
+        InputConfigError::Io(err)
+    }
+}
+
+/// Which bindings trigger which action
+#[derive(Debug, Clone)]
+pub struct InputMap {
+    pub bindings: HashMap<String, Vec<Binding>>,
+}
+
+impl Default for InputMap {
+    fn default() -> InputMap {
+        let mut bindings: HashMap<String, Vec<Binding>> = HashMap::new();
+        for &(action, name) in DEFAULT_BINDINGS {
+            let binding = Binding::parse(name).expect("invalid default binding");
+            bindings.entry(action.into()).or_insert_with(Vec::new).push(binding);
+        }
+        InputMap { bindings }
+    }
+}
+
+impl InputMap {
+    /// The default bindings, with the actions in the config file at `path` rebound.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<InputMap, InputConfigError> {
+        let mut map = InputMap::default();
+        map.apply(&fs::read_to_string(path)?)?;
+        Ok(map)
+    }
+
+    /// The bindings that trigger more than one action, with those actions (sorted by name)
+    pub fn conflicts(&self) -> Vec<(Binding, Vec<String>)> {
+        let mut actions: Vec<(&String, &Vec<Binding>)> = self.bindings.iter().collect();
+        actions.sort_by(|a, b| a.0.cmp(b.0));
+        let mut actionsByBinding: Vec<(Binding, Vec<String>)> = Vec::new();
+        for (action, bindings) in actions {
+            for &binding in bindings {
+                match actionsByBinding.iter_mut().find(|entry| entry.0 == binding) {
+                    Some(entry) => entry.1.push(action.clone()),
+                    None => actionsByBinding.push((binding, vec![action.clone()])),
+                }
+            }
+        }
+        actionsByBinding.retain(|entry| entry.1.len() > 1);
+        actionsByBinding
+    }
+
+    /// Rebinds the actions listed in a config like the one described in the module documentation.
+    pub fn apply(&mut self, config: &str) -> Result<(), InputConfigError> {
+        for (i, line) in config.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parseError = |message: String| InputConfigError::Parse { line: i + 1, message };
+            let mut parts = line.splitn(2, '=');
+            let (action, names) = match (parts.next(), parts.next()) {
+                (Some(action), Some(names)) if !action.trim().is_empty() => (action.trim().to_lowercase(), names),
+                _ => return Err(parseError("expected 'action = binding, ...'".into())),
+            };
+            let bindings = names.split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(|name| Binding::parse(name).ok_or_else(|| parseError(format!("unknown key or button '{}'", name))))
+                .collect::<Result<Vec<_>, _>>()?;
+            // an empty list unbinds the action
+            self.bindings.insert(action, bindings);
+        }
+        Ok(())
+    }
+}
+
+/// The input map and the state of all actions in the current and previous frame
+struct InputState {
+    map: InputMap,
+    held: Vec<String>,
+    previouslyHeld: Vec<String>,
+    /// like `held`, but only counting key and mouse button bindings
+    keysHeld: Vec<String>,
+    previousKeysHeld: Vec<String>,
+    /// scrolled since the last `update`, positive is up
+    scrollAccumulator: f32,
+    scroll: f32,
+}
+
+thread_local! {
+    static INPUT: RefCell<InputState> = RefCell::new(InputState {
+        map: loadConfig(),
+        held: Vec::new(),
+        previouslyHeld: Vec::new(),
+        keysHeld: Vec::new(),
+        previousKeysHeld: Vec::new(),
+        scrollAccumulator: 0.0,
+        scroll: 0.0,
+    });
+}
+
+fn loadConfig() -> InputMap {
+    if !Path::new(CONFIG_FILE).exists() {
+        return InputMap::default();
+    }
+    let map = InputMap::load(CONFIG_FILE).unwrap_or_else(|err| {
+        println!("ERROR::INPUT: failed to load {}: {}; using the default bindings", CONFIG_FILE, err);
+        InputMap::default()
+    });
+    // allowed, but probably not intended if the config file caused it
+    let defaultConflicts = InputMap::default().conflicts();
+    for (binding, actions) in map.conflicts().into_iter().filter(|conflict| !defaultConflicts.contains(conflict)) {
+        println!("WARNING::INPUT: {} triggers several actions: {}", binding, actions.join(", "));
+    }
+    map
+}
+
+/// Samples all bound keys and buttons; call once per frame after the window events were processed.
+pub fn update(window: &glfw::Window) {
+    INPUT.with(|state| {
+        let state = &mut *state.borrow_mut();
+        state.scroll = state.scrollAccumulator;
+        state.scrollAccumulator = 0.0;
+        let scroll = state.scroll;
+        let actionsWhere = |down: &dyn Fn(Binding) -> bool| -> Vec<String> {
+            state.map.bindings.iter()
+                .filter(|&(_, bindings)| bindings.iter().any(|&binding| down(binding)))
+                .map(|(action, _)| action.clone())
+                .collect()
+        };
+        let held = actionsWhere(&|binding| isDown(window, binding, scroll));
+        let keysHeld = actionsWhere(&|binding| !isScroll(binding) && isDown(window, binding, scroll));
+        state.previouslyHeld = mem::replace(&mut state.held, held);
+        state.previousKeysHeld = mem::replace(&mut state.keysHeld, keysHeld);
+    });
+}
+
+fn isScroll(binding: Binding) -> bool {
+    binding == Binding::ScrollUp || binding == Binding::ScrollDown
+}
+
+fn isDown(window: &glfw::Window, binding: Binding, scroll: f32) -> bool {
+    match binding {
+        Binding::Key(key) => window.get_key(key) != Action::Release,
+        Binding::MouseButton(button) => window.get_mouse_button(button) != Action::Release,
+        Binding::ScrollUp => scroll > 0.0,
+        Binding::ScrollDown => scroll < 0.0,
+    }
+}
+
+/// for `process_events`: records scroll wheel movement for the next `update`
+pub fn scroll_event(yoffset: f32) {
+    INPUT.with(|state| state.borrow_mut().scrollAccumulator += yoffset);
+}
+
+/// true while a binding of the action is held down (and in frames in which a bound wheel direction was scrolled)
+pub fn held(action: &str) -> bool {
+    INPUT.with(|state| state.borrow().held.iter().any(|a| a == action))
+}
+
+/// true only in the frame in which the action started, e.g. for toggles
+pub fn pressed(action: &str) -> bool {
+    INPUT.with(|state| {
+        let state = state.borrow();
+        state.held.iter().any(|a| a == action) && !state.previouslyHeld.iter().any(|a| a == action)
+    })
+}
+
+/// true only in the frame in which the action ended
+pub fn released(action: &str) -> bool {
+    INPUT.with(|state| {
+        let state = state.borrow();
+        !state.held.iter().any(|a| a == action) && state.previouslyHeld.iter().any(|a| a == action)
+    })
+}
+
+/// How many steps the action moved this frame: the scroll amount for wheel bindings, 1 when a key
+/// or button binding was pressed. For actions like zooming that the wheel and keys can both trigger.
+pub fn steps(action: &str) -> f32 {
+    INPUT.with(|state| {
+        let state = state.borrow();
+        let mut steps = 0.0;
+        for &binding in state.map.bindings.get(action).map(Vec::as_slice).unwrap_or(&[]) {
+            match binding {
+                Binding::ScrollUp if state.scroll > 0.0 => steps += state.scroll,
+                Binding::ScrollDown if state.scroll < 0.0 => steps -= state.scroll,
+                _ => {}
+            }
+        }
+        if state.keysHeld.iter().any(|a| a == action) && !state.previousKeysHeld.iter().any(|a| a == action) {
+            steps += 1.0;
+        }
+        steps
+    })
+}
+
+/// replaces the bindings, e.g. with an `InputMap` loaded from another file
+pub fn set_map(map: InputMap) {
+    INPUT.with(|state| state.borrow_mut().map = map);
+}
@@ -15,3 +15,54 @@ macro_rules! offset_of {
         &(*(ptr::null() as *const $ty)).$field as *const _ as usize
     }
 }
+
+/// Declares a struct that can be uploaded to a `layout (std140)` uniform block with
+/// `uniform_buffer::UniformBuffer`. The fields have to be declared in the same order as the block
+/// members in the shader; their padded offsets and the size of the block are computed at compile time.
+macro_rules! uniform_block {
+    (@end $offset:expr;) => { $offset };
+    (@end $offset:expr; $ty:ty $(, $rest:ty)*) => {
+        uniform_block!(@end
+            $crate::uniform_buffer::std140_align($offset, <$ty as $crate::uniform_buffer::Std140>::ALIGN)
+                + <$ty as $crate::uniform_buffer::Std140>::SIZE;
+            $($rest),*)
+    };
+    ($(#[$attr:meta])* $vis:vis struct $name:ident {
+        $($(#[$fieldAttr:meta])* $fieldVis:vis $field:ident: $ty:ty),* $(,)*
+    }) => {
+        $(#[$attr])*
+        $vis struct $name {
+            $($(#[$fieldAttr])* $fieldVis $field: $ty),*
+        }
+
+        impl $crate::uniform_buffer::Std140 for $name {
+            // structs are aligned and padded like a vec4
+            const ALIGN: usize = 16;
+            const SIZE: usize = $crate::uniform_buffer::std140_align(uniform_block!(@end 0; $($ty),*), 16);
+
+            fn write_std140(&self, buffer: &mut [u8]) {
+                use $crate::uniform_buffer::{std140_align, Std140};
+                let mut offset = 0;
+                $(
+                    offset = std140_align(offset, <$ty as Std140>::ALIGN);
+                    self.$field.write_std140(&mut buffer[offset..]);
+                    offset += <$ty as Std140>::SIZE;
+                )*
+                let _ = offset;
+            }
+
+            fn std140_members() -> Vec<(&'static str, usize)> {
+                use $crate::uniform_buffer::{std140_align, Std140};
+                let mut members = Vec::new();
+                let mut offset = 0;
+                $(
+                    offset = std140_align(offset, <$ty as Std140>::ALIGN);
+                    members.push((stringify!($field), offset));
+                    offset += <$ty as Std140>::SIZE;
+                )*
+                let _ = offset;
+                members
+            }
+        }
+    };
+}
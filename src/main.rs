@@ -4,13 +4,33 @@ extern crate gl;
 extern crate image;
 extern crate cgmath;
 extern crate tobj;
+#[cfg(feature = "gltf")]
+extern crate gltf;
 
+mod bookmarks;
+mod bounds;
 mod common;
+mod preprocessor;
+mod program_cache;
 mod shader;
+mod shader_permutations;
+mod shader_reload;
+mod uniform;
+mod uniform_buffer;
 mod macros;
 mod camera;
+mod flythrough;
+mod frustum;
+mod gamepad;
+mod input;
+mod orbit_camera;
+mod quat_camera;
 mod mesh;
 mod model;
+mod model_data;
+mod normals;
+mod picking;
+mod tangents;
 mod utils;
 
 #[cfg(feature = "chapter-1")]
@@ -50,9 +70,18 @@ use _7_in_practice::*;
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
-    if args.len() != 2 {
+    let usage = || {
         println!("Call with the number of the tutorial, e.g. `1_1_2` for _1_2_hello_window_clear.rs");
+        println!("and optionally `--bookmark N` to start at camera bookmark N (see bookmarks.rs)");
         std::process::exit(1);
+    };
+    match args.len() {
+        2 => {}
+        4 if args[2] == "--bookmark" => match args[3].parse() {
+            Ok(slot) => common::setStartBookmark(slot),
+            Err(_) => usage(),
+        },
+        _ => usage(),
     }
     let tutorial_id = &args[1];
 
@@ -6,40 +6,15 @@ use std::mem::size_of;
 use std::os::raw::c_void;
 use std::ptr;
 
-use cgmath::{ Vector3, Vector2 };
+use cgmath::{ Point3, Matrix4 };
 use cgmath::prelude::*;
 use gl;
 
+use bounds::{Aabb, BoundingSphere};
+use picking::{Ray, ray_aabb, ray_triangles};
 use shader::Shader;
 
-// NOTE: without repr(C) the compiler may reorder the fields or use different padding/alignment than C.
-// Depending on how you pass the data to OpenGL, this may be bad. In this case it's not strictly
-// necessary though because of the `offset!` macro used below in setupMesh()
-#[repr(C)]
-pub struct Vertex {
-    // position
-    pub Position: Vector3<f32>,
-    // normal
-    pub Normal: Vector3<f32>,
-    // texCoords
-    pub TexCoords: Vector2<f32>,
-    // tangent
-    pub Tangent: Vector3<f32>,
-    // bitangent
-    pub Bitangent: Vector3<f32>,
-}
-
-impl Default for Vertex {
-    fn default() -> Self {
-        Vertex {
-            Position: Vector3::zero(),
-            Normal: Vector3::zero(),
-            TexCoords: Vector2::zero(),
-            Tangent: Vector3::zero(),
-            Bitangent: Vector3::zero(),
-        }
-    }
-}
+pub use model_data::{Material, Vertex};
 
 #[derive(Clone)]
 pub struct Texture {
@@ -53,6 +28,11 @@ pub struct Mesh {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
     pub textures: Vec<Texture>,
+    pub material: Material,
+    /// bounds of the vertex positions, in the mesh's local space; `aabb.transform(&model)` gives
+    /// them in world space
+    pub aabb: Aabb,
+    pub bounding_sphere: BoundingSphere,
     pub VAO: u32,
 
     /*  Render data  */
@@ -62,8 +42,17 @@ pub struct Mesh {
 
 impl Mesh {
     pub fn new(vertices: Vec<Vertex>, indices: Vec<u32>, textures: Vec<Texture>) -> Mesh {
+        let aabb = Aabb::from_points(vertices.iter().map(|v| Point3::from_vec(v.Position)));
+        let bounding_sphere = BoundingSphere::from_points(vertices.iter().map(|v| Point3::from_vec(v.Position)));
+        Mesh::with_bounds(vertices, indices, textures, aabb, bounding_sphere)
+    }
+
+    /// Like `new`, with bounds already computed from the vertices (e.g. by `MeshData::aabb`)
+    pub fn with_bounds(vertices: Vec<Vertex>, indices: Vec<u32>, textures: Vec<Texture>,
+                       aabb: Aabb, bounding_sphere: BoundingSphere) -> Mesh {
         let mut mesh = Mesh {
-            vertices, indices, textures,
+            vertices, indices, textures, aabb, bounding_sphere,
+            material: Material::default(),
             VAO: 0, VBO: 0, EBO: 0
         };
 
@@ -79,6 +68,10 @@ impl Mesh {
         let mut specularNr = 0;
         let mut normalNr   = 0;
         let mut heightNr   = 0;
+        // glTF materials
+        let mut metallicRoughnessNr = 0;
+        let mut occlusionNr = 0;
+        let mut emissiveNr = 0;
         for (i, texture) in self.textures.iter().enumerate() {
             gl::ActiveTexture(gl::TEXTURE0 + i as u32); // active proper texture unit before binding
             // retrieve texture number (the N in diffuse_textureN)
@@ -100,11 +93,23 @@ impl Mesh {
                     heightNr += 1;
                     heightNr
                 }
+                "texture_metallic_roughness" => {
+                    metallicRoughnessNr += 1;
+                    metallicRoughnessNr
+                }
+                "texture_occlusion" => {
+                    occlusionNr += 1;
+                    occlusionNr
+                }
+                "texture_emissive" => {
+                    emissiveNr += 1;
+                    emissiveNr
+                }
                 _ => panic!("unknown texture type")
             };
             // now set the sampler to the correct texture unit
             let sampler = CString::new(format!("{}{}", name, number)).unwrap();
-            gl::Uniform1i(gl::GetUniformLocation(shader.ID, sampler.as_ptr()), i as i32);
+            shader.setInt(&sampler, i as i32);
             // and finally bind the texture
             gl::BindTexture(gl::TEXTURE_2D, texture.id);
         }
@@ -118,6 +123,15 @@ impl Mesh {
         gl::ActiveTexture(gl::TEXTURE0);
     }
 
+    /// The closest triangle hit by a world space `ray`, as (triangle index, distance along the ray),
+    /// with the mesh transformed by `model`.
+    pub fn Intersect(&self, ray: &Ray, model: &Matrix4<f32>) -> Option<(usize, f32)> {
+        // intersect in the mesh's local space instead of transforming all vertices
+        let localRay = ray.transform(&model.invert()?);
+        ray_aabb(&localRay, &self.aabb)?;
+        ray_triangles(&localRay, &self.indices, |i| Point3::from_vec(self.vertices[i as usize].Position))
+    }
+
     unsafe fn setupMesh(&mut self) {
         // create buffers/arrays
         gl::GenVertexArrays(1, &mut self.VAO);
@@ -130,13 +144,14 @@ impl Mesh {
         // A great thing about structs with repr(C) is that their memory layout is sequential for all its items.
         // The effect is that we can simply pass a pointer to the struct and it translates perfectly to a glm::vec3/2 array which
         // again translates to 3/2 floats which translates to a byte array.
+        // (`as_ptr` instead of `&self.vertices[0]`, which would panic for an empty mesh)
         let size = (self.vertices.len() * size_of::<Vertex>()) as isize;
-        let data = &self.vertices[0] as *const Vertex as *const c_void;
+        let data = self.vertices.as_ptr() as *const c_void;
         gl::BufferData(gl::ARRAY_BUFFER, size, data, gl::STATIC_DRAW);
 
         gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.EBO);
         let size = (self.indices.len() * size_of::<u32>()) as isize;
-        let data = &self.indices[0] as *const u32 as *const c_void;
+        let data = self.indices.as_ptr() as *const c_void;
         gl::BufferData(gl::ELEMENT_ARRAY_BUFFER, size, data, gl::STATIC_DRAW);
 
         // set the vertex attribute pointers
@@ -4,14 +4,18 @@
 use std::os::raw::c_void;
 use std::path::Path;
 
-use cgmath::{vec2, vec3};
+use cgmath::Matrix4;
 use gl;
 use image;
 use image::DynamicImage::*;
 use image::GenericImage;
-use tobj;
 
-use mesh::{ Mesh, Texture, Vertex };
+use bounds::{Aabb, BoundingSphere};
+use frustum::{CullStats, Frustum};
+use mesh::{ Mesh, Texture };
+pub use model_data::{LoadOptions, ModelData, ModelError};
+use model_data::{ImageData, TextureDescriptor, TextureSource};
+use picking::{PickHit, Ray};
 use shader::Shader;
 
 #[derive(Default)]
@@ -19,14 +23,41 @@ pub struct Model {
     /*  Model Data */
     pub meshes: Vec<Mesh>,
     pub textures_loaded: Vec<Texture>,   // stores all the textures loaded so far, optimization to make sure textures aren't loaded more than once.
+    /// bounds of all meshes in model space, e.g. for placing the model or framing it with a camera;
+    /// `aabb.transform(&model)`/`bounding_sphere.transform(&model)` give them in world space
+    pub aabb: Aabb,
+    pub bounding_sphere: BoundingSphere,
     directory: String,
 }
 
 impl Model {
-    /// constructor, expects a filepath to a 3D model.
+    /// constructor, expects a filepath to a 3D model: Wavefront OBJ, or glTF 2.0 (`.gltf`/`.glb`).
+    /// Panics if the file can't be loaded, see `load`.
     pub fn new(path: &str) -> Model {
-        let mut model = Model::default();
-        model.loadModel(path);
+        Model::load(path, &LoadOptions::default())
+            .unwrap_or_else(|err| panic!("{}: {}", path, err))
+    }
+
+    /// Like `new`, but returns an error if the file can't be read or parsed. Shapes without
+    /// triangles are skipped with a warning.
+    pub fn load(path: &str, options: &LoadOptions) -> Result<Model, ModelError> {
+        ModelData::load(path, options).map(Model::from_data)
+    }
+
+    /// Uploads imported (and possibly processed) model data: creates the vertex buffers and loads the textures.
+    pub fn from_data(data: ModelData) -> Model {
+        // tighter than a sphere around the mesh spheres
+        let bounding_sphere = data.bounding_sphere();
+        let ModelData { meshes, images, directory } = data;
+        let mut model = Model { bounding_sphere, directory, ..Model::default() };
+        for mesh in meshes {
+            let (aabb, sphere) = (mesh.aabb(), mesh.bounding_sphere());
+            model.aabb = model.aabb.union(&aabb);
+            let textures = mesh.textures.iter().map(|texture| model.loadTexture(texture, &images)).collect();
+            let mut uploaded = Mesh::with_bounds(mesh.vertices, mesh.indices, textures, aabb, sphere);
+            uploaded.material = mesh.material;
+            model.meshes.push(uploaded);
+        }
         model
     }
 
@@ -36,76 +67,47 @@ impl Model {
         }
     }
 
-    // loads a model from file and stores the resulting meshes in the meshes vector.
-    fn loadModel(&mut self, path: &str) {
-        let path = Path::new(path);
-
-        // retrieve the directory path of the filepath
-        self.directory = path.parent().unwrap_or_else(|| Path::new("")).to_str().unwrap().into();
-        let obj = tobj::load_obj(path);
-
-        let (models, materials) = obj.unwrap();
-        for model in models {
-            let mesh = &model.mesh;
-            let num_vertices = mesh.positions.len() / 3;
-
-            // data to fill
-            let mut vertices: Vec<Vertex> = Vec::with_capacity(num_vertices);
-            let indices: Vec<u32> = mesh.indices.clone();
-
-            let (p, n, t) = (&mesh.positions, &mesh.normals, &mesh.texcoords);
-            for i in 0..num_vertices {
-                vertices.push(Vertex {
-                    Position:  vec3(p[i*3], p[i*3+1], p[i*3+2]),
-                    Normal:    vec3(n[i*3], n[i*3+1], n[i*3+2]),
-                    TexCoords: vec2(t[i*2], t[i*2+1]),
-                    ..Vertex::default()
-                })
-            }
-
-            // process material
-            let mut textures = Vec::new();
-            if let Some(material_id) = mesh.material_id {
-                let material = &materials[material_id];
-
-                // 1. diffuse map
-                if !material.diffuse_texture.is_empty() {
-                    let texture = self.loadMaterialTexture(&material.diffuse_texture, "texture_diffuse");
-                    textures.push(texture);
-                }
-                // 2. specular map
-                if !material.specular_texture.is_empty() {
-                    let texture = self.loadMaterialTexture(&material.specular_texture, "texture_specular");
-                    textures.push(texture);
-                }
-                // 3. normal map
-                if !material.normal_texture.is_empty() {
-                    let texture = self.loadMaterialTexture(&material.normal_texture, "texture_normal");
-                    textures.push(texture);
-                }
-                // NOTE: no height maps
+    /// Like `Draw`, but skips meshes whose bounds, transformed by `model` (the matrix also passed to
+    /// the shader), are outside the world space `frustum`. Counts the tested meshes in `stats`.
+    pub fn DrawCulled(&self, shader: &Shader, frustum: &Frustum, model: &Matrix4<f32>, stats: &mut CullStats) {
+        for mesh in &self.meshes {
+            if stats.record(frustum.intersects_aabb(&mesh.aabb.transform(model))) {
+                unsafe { mesh.Draw(shader); }
             }
-
-            self.meshes.push(Mesh::new(vertices, indices, textures));
         }
+    }
 
+    /// The closest mesh and triangle hit by a world space `ray` (see `picking::screen_ray`), with
+    /// the model transformed by `model`.
+    pub fn Pick(&self, ray: &Ray, model: &Matrix4<f32>) -> Option<PickHit> {
+        self.meshes.iter()
+            .enumerate()
+            .filter_map(|(i, mesh)| mesh.Intersect(ray, model).map(|(triangle, distance)| PickHit { mesh: i, triangle, distance }))
+            .fold(None, |closest: Option<PickHit>, hit| match closest {
+                Some(closest) if closest.distance <= hit.distance => Some(closest),
+                _ => Some(hit),
+            })
     }
 
-    fn loadMaterialTexture(&mut self, path: &str, typeName: &str) -> Texture {
-        {
-            let texture = self.textures_loaded.iter().find(|t| t.path == path);
-            if let Some(texture) = texture {
-                return texture.clone();
+    fn loadTexture(&mut self, texture: &TextureDescriptor, images: &[ImageData]) -> Texture {
+        // images embedded in glTF files have no path, the index identifies them within the file
+        let path = match texture.source {
+            TextureSource::File(ref path) => path.clone(),
+            TextureSource::Image(index) => format!("#image{}", index),
+        };
+        let id = match self.textures_loaded.iter().find(|t| t.path == path) {
+            Some(loaded) => loaded.id,
+            None => {
+                let id = match texture.source {
+                    TextureSource::File(ref path) => unsafe { TextureFromFile(path, &self.directory) },
+                    TextureSource::Image(index) => unsafe { TextureFromGltfImage(&images[index]) },
+                };
+                self.textures_loaded.push(Texture { id, type_: texture.type_.clone(), path: path.clone() });
+                id
             }
-        }
-
-        let texture = Texture {
-            id: unsafe { TextureFromFile(path, &self.directory) },
-            type_: typeName.into(),
-            path: path.into()
         };
-        self.textures_loaded.push(texture.clone());
-        texture
+        // the same image may be used as different types of map
+        Texture { id, type_: texture.type_.clone(), path }
     }
 }
 
@@ -138,3 +140,45 @@ unsafe fn TextureFromFile(path: &str, directory: &str) -> u32 {
 
     textureID
 }
+
+/// uploads an image decoded by the glTF importer; unlike OBJ textures, glTF images aren't flipped
+#[cfg(feature = "gltf")]
+unsafe fn TextureFromGltfImage(image: &ImageData) -> u32 {
+    use gltf::image::Format::*;
+    // sized internal formats, so 16 bit and float images keep their precision
+    let (internalFormat, format, type_) = match image.format {
+        R8 => (gl::R8, gl::RED, gl::UNSIGNED_BYTE),
+        R8G8 => (gl::RG8, gl::RG, gl::UNSIGNED_BYTE),
+        R8G8B8 => (gl::RGB8, gl::RGB, gl::UNSIGNED_BYTE),
+        R8G8B8A8 => (gl::RGBA8, gl::RGBA, gl::UNSIGNED_BYTE),
+        R16 => (gl::R16, gl::RED, gl::UNSIGNED_SHORT),
+        R16G16 => (gl::RG16, gl::RG, gl::UNSIGNED_SHORT),
+        R16G16B16 => (gl::RGB16, gl::RGB, gl::UNSIGNED_SHORT),
+        R16G16B16A16 => (gl::RGBA16, gl::RGBA, gl::UNSIGNED_SHORT),
+        R32G32B32FLOAT => (gl::RGB32F, gl::RGB, gl::FLOAT),
+        R32G32B32A32FLOAT => (gl::RGBA32F, gl::RGBA, gl::FLOAT),
+    };
+
+    let mut textureID = 0;
+    gl::GenTextures(1, &mut textureID);
+    gl::BindTexture(gl::TEXTURE_2D, textureID);
+    // rows of RGB images with an odd width aren't 4 byte aligned
+    gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+    // unlike `&pixels[0]`, `as_ptr` doesn't panic for images without pixels
+    gl::TexImage2D(gl::TEXTURE_2D, 0, internalFormat as i32, image.width as i32, image.height as i32,
+        0, format, type_, image.pixels.as_ptr() as *const c_void);
+    gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+    gl::GenerateMipmap(gl::TEXTURE_2D);
+
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as i32);
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+
+    textureID
+}
+
+#[cfg(not(feature = "gltf"))]
+unsafe fn TextureFromGltfImage(image: &ImageData) -> u32 {
+    match *image {}
+}
@@ -0,0 +1,413 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// The CPU side of model loading: the OBJ and glTF importers produce plain vertices, indices and
+/// material descriptions without touching OpenGL, so they (and any processing of their results)
+/// work without a GL context, e.g. in tests. `Model::from_data` then uploads a `ModelData`:
+///
+///     let data = ModelData::load("resources/objects/rock/rock.obj", &LoadOptions::default())?;
+///     // ... process data.meshes ...
+///     let model = Model::from_data(data);
+
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+use cgmath::{vec2, vec3, Deg, Point3, Vector2, Vector3, Vector4};
+#[cfg(feature = "gltf")]
+use cgmath::{Matrix3, Matrix4};
+use cgmath::prelude::*;
+#[cfg(feature = "gltf")]
+use gltf;
+use tobj;
+
+use bounds::{Aabb, BoundingSphere};
+use normals::generate_normals;
+use tangents::{bitangent, generate_tangents};
+
+// NOTE: without repr(C) the compiler may reorder the fields or use different padding/alignment than C.
+// Depending on how you pass the data to OpenGL, this may be bad. In this case it's not strictly
+// necessary though because of the `offset!` macro used in Mesh::setupMesh()
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vertex {
+    // position
+    pub Position: Vector3<f32>,
+    // normal
+    pub Normal: Vector3<f32>,
+    // texCoords
+    pub TexCoords: Vector2<f32>,
+    // tangent
+    pub Tangent: Vector3<f32>,
+    // bitangent
+    pub Bitangent: Vector3<f32>,
+}
+
+impl Default for Vertex {
+    fn default() -> Self {
+        Vertex {
+            Position: Vector3::zero(),
+            Normal: Vector3::zero(),
+            TexCoords: Vector2::zero(),
+            Tangent: Vector3::zero(),
+            Bitangent: Vector3::zero(),
+        }
+    }
+}
+
+/// The factors of glTF's metallic-roughness material model; the texture maps (if any) are
+/// multiplied by them. Not set as uniforms by `Mesh::Draw`, shaders that need them have to set them.
+/// OBJ models get the defaults: a white, rough non-metal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Material {
+    pub base_color_factor: Vector4<f32>,
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub emissive_factor: Vector3<f32>,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            base_color_factor: Vector4::new(1.0, 1.0, 1.0, 1.0),
+            metallic_factor: 0.0,
+            roughness_factor: 1.0,
+            emissive_factor: Vector3::zero(),
+        }
+    }
+}
+
+/// Where the pixels of a texture come from
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextureSource {
+    /// an image file relative to `ModelData::directory` (OBJ materials); flipped vertically on upload
+    File(String),
+    /// an image decoded by the glTF importer, index into `ModelData::images`
+    Image(usize),
+}
+
+/// the pixels of an image embedded in or referenced by a glTF file
+#[cfg(feature = "gltf")]
+pub type ImageData = gltf::image::Data;
+/// without the `gltf` feature there are no decoded images
+#[cfg(not(feature = "gltf"))]
+#[derive(Debug, Clone)]
+pub enum ImageData {}
+
+/// A texture map of a mesh, before it is loaded
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureDescriptor {
+    /// the sampler name prefix in the shaders, e.g. "texture_diffuse" (see `Mesh::Draw`)
+    pub type_: String,
+    pub source: TextureSource,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MeshData {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    pub textures: Vec<TextureDescriptor>,
+    pub material: Material,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ModelData {
+    pub meshes: Vec<MeshData>,
+    /// images decoded from a glTF file (OBJ textures are read from their files on upload)
+    pub images: Vec<ImageData>,
+    /// the directory of the model file, for the `TextureSource::File` paths
+    pub directory: String,
+}
+
+/// Options for `ModelData::load`/`Model::load`
+#[derive(Debug, Clone, Copy)]
+pub struct LoadOptions {
+    /// For OBJ meshes without normals: edges where the faces meet at a larger angle stay sharp,
+    /// see `normals::generate_normals`. 0° gives flat shading.
+    pub crease_angle: Deg<f32>,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        LoadOptions { crease_angle: Deg(60.0) }
+    }
+}
+
+#[derive(Debug)]
+pub enum ModelError {
+    Obj(tobj::LoadError),
+    #[cfg(feature = "gltf")]
+    Gltf(gltf::Error),
+    /// a glTF file, but the `gltf` feature is disabled
+    GltfDisabled,
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ModelError::Obj(ref err) => write!(f, "failed to load OBJ file: {}", err),
+            #[cfg(feature = "gltf")]
+            ModelError::Gltf(ref err) => write!(f, "failed to load glTF file: {}", err),
+            ModelError::GltfDisabled => write!(f, "glTF files need the `gltf` feature"),
+        }
+    }
+}
+
+impl Error for ModelError {}
+
+impl From<tobj::LoadError> for ModelError {
+    fn from(err: tobj::LoadError) -> ModelError {
+        ModelError::Obj(err)
+    }
+}
+
+#[cfg(feature = "gltf")]
+impl From<gltf::Error> for ModelError {
+    fn from(err: gltf::Error) -> ModelError {
+        ModelError::Gltf(err)
+    }
+}
+
+impl MeshData {
+    /// bounds of the vertex positions
+    pub fn aabb(&self) -> Aabb {
+        Aabb::from_points(self.vertices.iter().map(|v| Point3::from_vec(v.Position)))
+    }
+
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere::from_points(self.vertices.iter().map(|v| Point3::from_vec(v.Position)))
+    }
+}
+
+impl ModelData {
+    /// bounds of all meshes
+    pub fn aabb(&self) -> Aabb {
+        self.meshes.iter().fold(Aabb::empty(), |aabb, mesh| aabb.union(&mesh.aabb()))
+    }
+
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere::from_points(self.meshes.iter().flat_map(|mesh| mesh.vertices.iter()).map(|v| Point3::from_vec(v.Position)))
+    }
+
+    /// Imports a Wavefront OBJ or glTF 2.0 (`.gltf`/`.glb`) file. Shapes without triangles are
+    /// skipped with a warning.
+    pub fn load(path: &str, options: &LoadOptions) -> Result<ModelData, ModelError> {
+        let path = Path::new(path);
+        let mut data = ModelData {
+            // retrieve the directory path of the filepath
+            directory: path.parent().unwrap_or_else(|| Path::new("")).to_str().unwrap().into(),
+            ..ModelData::default()
+        };
+        let extension = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+        match extension.as_ref().map(String::as_str) {
+            #[cfg(feature = "gltf")]
+            Some("gltf") | Some("glb") => data.loadGltf(path)?,
+            #[cfg(not(feature = "gltf"))]
+            Some("gltf") | Some("glb") => return Err(ModelError::GltfDisabled),
+            _ => data.loadObj(path, options)?,
+        }
+        Ok(data)
+    }
+
+    fn loadObj(&mut self, path: &Path, options: &LoadOptions) -> Result<(), ModelError> {
+        let (models, materials) = tobj::load_obj(path)?;
+        for model in models {
+            let mesh = &model.mesh;
+            let num_vertices = mesh.positions.len() / 3;
+            if num_vertices == 0 || mesh.indices.len() < 3 {
+                println!("WARNING::MODEL: skipping shape '{}' without triangles in {}", model.name, path.display());
+                continue;
+            }
+
+            // data to fill
+            let mut vertices: Vec<Vertex> = Vec::with_capacity(num_vertices);
+            let mut indices: Vec<u32> = mesh.indices.clone();
+
+            let (p, n, t) = (&mesh.positions, &mesh.normals, &mesh.texcoords);
+            for i in 0..num_vertices {
+                vertices.push(Vertex {
+                    Position:  vec3(p[i*3], p[i*3+1], p[i*3+2]),
+                    // the texture coordinates default to (0, 0) if the file has none
+                    TexCoords: if t.len() == num_vertices * 2 { vec2(t[i*2], t[i*2+1]) } else { vec2(0.0, 0.0) },
+                    ..Vertex::default()
+                })
+            }
+            if n.len() == num_vertices * 3 {
+                for (i, vertex) in vertices.iter_mut().enumerate() {
+                    vertex.Normal = vec3(n[i*3], n[i*3+1], n[i*3+2]);
+                }
+            } else {
+                let positions: Vec<_> = vertices.iter().map(|v| v.Position).collect();
+                let generated = generate_normals(&positions, &indices, options.crease_angle);
+                // vertices on sharp edges are split
+                vertices = generated.vertices.iter().zip(generated.normals)
+                    .map(|(&i, normal)| Vertex { Normal: normal, ..vertices[i as usize] })
+                    .collect();
+                indices = generated.indices;
+            }
+
+            // tangent frames for normal mapping
+            let positions: Vec<_> = vertices.iter().map(|v| v.Position).collect();
+            let normals: Vec<_> = vertices.iter().map(|v| v.Normal).collect();
+            let texCoords: Vec<_> = vertices.iter().map(|v| v.TexCoords).collect();
+            for (vertex, tangent) in vertices.iter_mut().zip(generate_tangents(&positions, &normals, &texCoords, &indices)) {
+                vertex.Tangent = tangent.truncate();
+                vertex.Bitangent = bitangent(vertex.Normal, tangent);
+            }
+
+            // process material
+            let mut textures = Vec::new();
+            if let Some(material_id) = mesh.material_id {
+                let material = &materials[material_id];
+                let file = |path: &str, typeName: &str| TextureDescriptor { type_: typeName.into(), source: TextureSource::File(path.into()) };
+
+                // 1. diffuse map
+                if !material.diffuse_texture.is_empty() {
+                    textures.push(file(&material.diffuse_texture, "texture_diffuse"));
+                }
+                // 2. specular map
+                if !material.specular_texture.is_empty() {
+                    textures.push(file(&material.specular_texture, "texture_specular"));
+                }
+                // 3. normal map
+                if !material.normal_texture.is_empty() {
+                    textures.push(file(&material.normal_texture, "texture_normal"));
+                }
+                // NOTE: no height maps
+            }
+
+            self.meshes.push(MeshData { vertices, indices, textures, material: Material::default() });
+        }
+        Ok(())
+    }
+
+    /// glTF 2.0 (with the `gltf` feature): `.gltf` with external or embedded (base64) buffers and images, or binary `.glb`.
+    /// The node transforms of the default scene are baked into the vertices, so the meshes are
+    /// drawn with just the model matrix like OBJ meshes. Every primitive becomes a mesh.
+    #[cfg(feature = "gltf")]
+    fn loadGltf(&mut self, path: &Path) -> Result<(), ModelError> {
+        let (document, buffers, images) = gltf::import(path)?;
+        self.images = images;
+        let scene = match document.default_scene().or_else(|| document.scenes().next()) {
+            Some(scene) => scene,
+            None => return Ok(()),
+        };
+
+        // depth first, with the accumulated transform of the parent nodes
+        let mut nodes: Vec<(gltf::Node, Matrix4<f32>)> = scene.nodes().map(|node| (node, Matrix4::identity())).collect();
+        while let Some((node, parentTransform)) = nodes.pop() {
+            let transform = parentTransform * Matrix4::from(node.transform().matrix());
+            if let Some(mesh) = node.mesh() {
+                for primitive in mesh.primitives() {
+                    if let Some(mesh) = loadGltfPrimitive(&primitive, &transform, &buffers) {
+                        self.meshes.push(mesh);
+                    }
+                }
+            }
+            nodes.extend(node.children().map(|child| (child, transform)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "gltf")]
+fn loadGltfPrimitive(primitive: &gltf::Primitive, transform: &Matrix4<f32>, buffers: &[gltf::buffer::Data]) -> Option<MeshData> {
+    if primitive.mode() != gltf::mesh::Mode::Triangles {
+        println!("WARNING::MODEL: skipping glTF primitive with mode {:?}, only triangles are supported", primitive.mode());
+        return None;
+    }
+    let linear = Matrix3::from_cols(transform.x.truncate(), transform.y.truncate(), transform.z.truncate());
+    let normalMatrix = match linear.invert() {
+        Some(inverse) => inverse.transpose(),
+        None => {
+            println!("WARNING::MODEL: skipping glTF primitive with a singular node transform (scaled to nothing)");
+            return None;
+        }
+    };
+    // a mirroring transform turns the tangent frame left-handed
+    let handedness = linear.determinant().signum();
+
+    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+    let mut positions: Vec<Vector3<f32>> = reader.read_positions()?.map(Vector3::from).collect();
+    let mut indices: Vec<u32> = match reader.read_indices() {
+        Some(indices) => indices.into_u32().collect(),
+        None => (0..positions.len() as u32).collect(),
+    };
+    let mut texCoords: Vec<Vector2<f32>> = match reader.read_tex_coords(0) {
+        Some(texCoords) => texCoords.into_f32().map(Vector2::from).collect(),
+        None => vec![Vector2::zero(); positions.len()],
+    };
+    if positions.is_empty() || indices.len() < 3 {
+        println!("WARNING::MODEL: skipping glTF primitive without triangles");
+        return None;
+    }
+    let mut tangents: Option<Vec<[f32; 4]>> = reader.read_tangents().map(|tangents| tangents.collect());
+    let normals: Vec<Vector3<f32>> = match reader.read_normals() {
+        Some(normals) => normals.map(Vector3::from).collect(),
+        None => {
+            // the spec asks for flat normals
+            let generated = generate_normals(&positions, &indices, Deg(0.0));
+            positions = generated.vertices.iter().map(|&i| positions[i as usize]).collect();
+            texCoords = generated.vertices.iter().map(|&i| texCoords[i as usize]).collect();
+            tangents = tangents.map(|tangents| generated.vertices.iter().map(|&i| tangents[i as usize]).collect());
+            indices = generated.indices;
+            generated.normals
+        }
+    };
+    // tangent frames in the primitive's own space: the xyz of a glTF tangent with the
+    // bitangent's handedness in w, as `generate_tangents` returns them
+    let tangents: Vec<Vector4<f32>> = match tangents {
+        Some(tangents) => tangents.into_iter().map(Vector4::from).collect(),
+        None => generate_tangents(&positions, &normals, &texCoords, &indices),
+    };
+
+    let vertices: Vec<Vertex> = (0..positions.len())
+        .map(|i| {
+            let normal = (normalMatrix * normals[i]).normalize();
+            // non-uniform scales skew the tangent frame: make the tangent orthogonal to the
+            // transformed normal again (Gram-Schmidt) and derive the bitangent from both
+            let tangent = linear * tangents[i].truncate();
+            let tangent = (tangent - normal * normal.dot(tangent)).normalize();
+            Vertex {
+                Position: transform.transform_point(Point3::from_vec(positions[i])).to_vec(),
+                Normal: normal,
+                TexCoords: texCoords[i],
+                Tangent: tangent,
+                Bitangent: bitangent(normal, tangent.extend(tangents[i].w * handedness)),
+            }
+        })
+        .collect();
+    if handedness < 0.0 {
+        // a mirroring transform turns counter-clockwise triangles clockwise
+        for triangle in indices.chunks_mut(3).filter(|triangle| triangle.len() == 3) {
+            triangle.swap(1, 2);
+        }
+    }
+
+    let material = primitive.material();
+    let pbr = material.pbr_metallic_roughness();
+    let maps = vec![
+        (pbr.base_color_texture().map(|info| info.texture()), "texture_diffuse"),
+        (pbr.metallic_roughness_texture().map(|info| info.texture()), "texture_metallic_roughness"),
+        (material.normal_texture().map(|normal| normal.texture()), "texture_normal"),
+        (material.occlusion_texture().map(|occlusion| occlusion.texture()), "texture_occlusion"),
+        (material.emissive_texture().map(|info| info.texture()), "texture_emissive"),
+    ];
+    let textures = maps.into_iter()
+        .filter_map(|(texture, typeName)| texture.map(|texture| TextureDescriptor {
+            type_: typeName.into(),
+            source: TextureSource::Image(texture.source().index()),
+        }))
+        .collect();
+
+    Some(MeshData {
+        vertices,
+        indices,
+        textures,
+        material: Material {
+            base_color_factor: pbr.base_color_factor().into(),
+            metallic_factor: pbr.metallic_factor(),
+            roughness_factor: pbr.roughness_factor(),
+            emissive_factor: material.emissive_factor().into(),
+        },
+    })
+}
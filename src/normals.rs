@@ -0,0 +1,82 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// Vertex normals for meshes that come without them (OBJ files often don't have any). Edges where
+/// the faces meet at more than the crease angle stay sharp, flatter ones are smoothed: 0° gives
+/// flat shading, 180° smooths everything. Vertices on sharp edges are split, so the result has its
+/// own vertex list that refers back to the original vertices.
+
+use std::collections::HashMap;
+
+use cgmath::{Deg, Rad, Vector3};
+use cgmath::prelude::*;
+
+pub struct GeneratedNormals {
+    /// for every new vertex, the index of the original vertex it was split from
+    pub vertices: Vec<u32>,
+    pub normals: Vec<Vector3<f32>>,
+    /// the triangles, indexing the new vertices
+    pub indices: Vec<u32>,
+}
+
+/// Normals for an indexed triangle list. Triangles that share a position are smoothed even if they
+/// don't share the vertex (e.g. at UV seams). Each face contributes with its angle at the vertex.
+pub fn generate_normals(positions: &[Vector3<f32>], indices: &[u32], creaseAngle: Deg<f32>) -> GeneratedNormals {
+    let triangles: Vec<[u32; 3]> = indices.chunks(3)
+        .filter(|triangle| triangle.len() == 3)
+        .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+        .collect();
+    let faceNormals: Vec<Vector3<f32>> = triangles.iter()
+        .map(|t| {
+            let (a, b, c) = (positions[t[0] as usize], positions[t[1] as usize], positions[t[2] as usize]);
+            let normal = (b - a).cross(c - a);
+            if normal.magnitude2() > 0.0 { normal.normalize() } else { Vector3::zero() }
+        })
+        .collect();
+
+    // the triangle corners at every position, as (triangle, corner)
+    let mut corners: HashMap<[u32; 3], Vec<(usize, usize)>> = HashMap::new();
+    for (i, triangle) in triangles.iter().enumerate() {
+        for (corner, &vertex) in triangle.iter().enumerate() {
+            corners.entry(positionKey(positions[vertex as usize])).or_insert_with(Vec::new).push((i, corner));
+        }
+    }
+
+    let minCos = Rad::from(creaseAngle).0.cos();
+    let mut result = GeneratedNormals { vertices: Vec::new(), normals: Vec::new(), indices: Vec::with_capacity(triangles.len() * 3) };
+    // new vertex for each (original vertex, normal) pair, so smooth areas stay indexed
+    let mut newVertices: HashMap<(u32, [u32; 3]), u32> = HashMap::new();
+    for (i, triangle) in triangles.iter().enumerate() {
+        for &vertex in triangle {
+            let faceNormal = faceNormals[i];
+            let mut normal = Vector3::zero();
+            for &(other, corner) in &corners[&positionKey(positions[vertex as usize])] {
+                let otherNormal = faceNormals[other];
+                if other == i || faceNormal.dot(otherNormal) >= minCos - 1e-6 {
+                    normal += otherNormal * cornerAngle(positions, &triangles[other], corner);
+                }
+            }
+            let normal = if normal.magnitude2() > 0.0 { normal.normalize() } else { faceNormal };
+
+            let index = *newVertices.entry((vertex, positionKey(normal))).or_insert_with(|| {
+                result.vertices.push(vertex);
+                result.normals.push(normal);
+                result.vertices.len() as u32 - 1
+            });
+            result.indices.push(index);
+        }
+    }
+    result
+}
+
+/// for hashing exact positions
+fn positionKey(p: Vector3<f32>) -> [u32; 3] {
+    // +0.0 and -0.0 are the same position
+    [(p.x + 0.0).to_bits(), (p.y + 0.0).to_bits(), (p.z + 0.0).to_bits()]
+}
+
+fn cornerAngle(positions: &[Vector3<f32>], triangle: &[u32; 3], corner: usize) -> f32 {
+    let p = |i: usize| positions[triangle[i % 3] as usize];
+    let (a, b) = (p(corner + 1) - p(corner), p(corner + 2) - p(corner));
+    let lengths = (a.magnitude2() * b.magnitude2()).sqrt();
+    if lengths > 0.0 { (a.dot(b) / lengths).max(-1.0).min(1.0).acos() } else { 0.0 }
+}
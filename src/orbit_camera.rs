@@ -0,0 +1,216 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// A camera that orbits around a target point, for inspecting models:
+/// - drag with the left mouse button to rotate around the target
+/// - drag with the middle mouse button to pan (moves the target)
+/// - scroll to dolly towards/away from the target
+/// - W/S dolly and A/D rotate as well
+/// (with the default bindings of `input`)
+///
+/// Works with `common::process_events`/`processInput` like `Camera`; the window needs a visible cursor:
+///
+///     let mut camera = OrbitCamera::default();
+///     camera.Frame(min, max);
+
+use cgmath;
+use cgmath::vec3;
+use cgmath::prelude::*;
+
+use camera::{Projection, CameraController, Camera_Movement, Camera_MouseButton, ASPECT};
+use camera::Camera_Movement::*;
+use camera::Camera_MouseButton::*;
+
+type Point3 = cgmath::Point3<f32>;
+type Vector3 = cgmath::Vector3<f32>;
+type Matrix4 = cgmath::Matrix4<f32>;
+
+// Default camera values
+const DISTANCE: f32 = 3.0;
+const SENSITIVTY: f32 = 0.25;
+const PAN_SPEED: f32 = 0.0015;
+const DOLLY_FACTOR: f32 = 0.9;
+const ROTATION_SPEED: f32 = 90.0;
+const ZOOM: f32 = 45.0;
+
+pub struct OrbitCamera {
+    // Orbit parameters
+    pub Target: Point3,
+    pub Distance: f32,
+    /// degrees around the world up axis; 0 looks from +Z towards -Z like the default `Camera`
+    pub Azimuth: f32,
+    /// degrees above the horizontal plane through the target, clamped to +-89
+    pub Elevation: f32,
+    // derived from the orbit parameters (same fields as `Camera`)
+    pub Position: Point3,
+    pub Front: Vector3,
+    pub Up: Vector3,
+    pub Right: Vector3,
+    pub WorldUp: Vector3,
+    // Camera options
+    /// degrees per pixel of mouse movement
+    pub MouseSensitivity: f32,
+    /// pan distance per pixel, relative to `Distance`
+    pub PanSpeed: f32,
+    /// degrees per second for A/D
+    pub RotationSpeed: f32,
+    pub MinDistance: f32,
+    pub MaxDistance: f32,
+    /// field of view in degrees, used by `Frame` and for the projection matrix
+    pub Zoom: f32,
+    // Projection
+    /// width / height of the viewport
+    pub Aspect: f32,
+    pub Projection: Projection,
+    rotating: bool,
+    panning: bool,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> OrbitCamera {
+        let mut camera = OrbitCamera {
+            Target: Point3::new(0.0, 0.0, 0.0),
+            Distance: DISTANCE,
+            Azimuth: 0.0,
+            Elevation: 0.0,
+            Position: Point3::new(0.0, 0.0, 0.0), // initialized later
+            Front: Vector3::zero(), // initialized later
+            Up: Vector3::zero(), // initialized later
+            Right: Vector3::zero(), // initialized later
+            WorldUp: Vector3::unit_y(),
+            MouseSensitivity: SENSITIVTY,
+            PanSpeed: PAN_SPEED,
+            RotationSpeed: ROTATION_SPEED,
+            MinDistance: 0.01,
+            MaxDistance: 1000.0,
+            Zoom: ZOOM,
+            Aspect: ASPECT,
+            Projection: Projection::default(),
+            rotating: false,
+            panning: false,
+        };
+        camera.updateCameraVectors();
+        camera
+    }
+}
+
+impl OrbitCamera {
+    pub fn new(target: Point3, distance: f32) -> OrbitCamera {
+        let mut camera = OrbitCamera { Target: target, Distance: distance, ..OrbitCamera::default() };
+        camera.updateCameraVectors();
+        camera
+    }
+
+    /// Returns the view matrix, like `Camera::GetViewMatrix`
+    pub fn GetViewMatrix(&self) -> Matrix4 {
+        Matrix4::look_at(self.Position, self.Target, self.Up)
+    }
+
+    /// Returns the projection matrix for the current field of view (`Zoom`) and aspect ratio
+    pub fn GetProjectionMatrix(&self) -> Matrix4 {
+        self.Projection.matrix(self.Zoom, self.Aspect)
+    }
+
+    /// Centers the axis-aligned box `min`..`max` and moves back until its bounding sphere fits
+    /// into the field of view at the current `Aspect`, keeping the current direction.
+    pub fn Frame(&mut self, min: Point3, max: Point3) {
+        let radius = (max - min).magnitude() / 2.0;
+        self.Target = min.midpoint(max);
+        // the narrower of the vertical and horizontal field of view: the latter in portrait windows
+        let halfFovy = self.Zoom.to_radians() / 2.0;
+        let halfFovx = (halfFovy.tan() * self.Aspect).atan();
+        // distance at which a sphere of this radius touches the edges of that field of view
+        self.Distance = (radius / halfFovy.min(halfFovx).sin()).max(self.MinDistance);
+        self.MaxDistance = self.MaxDistance.max(self.Distance * 10.0);
+        self.updateCameraVectors();
+    }
+
+    /// W/S dolly, A/D rotate around the target
+    pub fn ProcessKeyboard(&mut self, direction: Camera_Movement, deltaTime: f32) {
+        match direction {
+            FORWARD => self.Dolly(DOLLY_FACTOR.powf(deltaTime * 10.0)),
+            BACKWARD => self.Dolly(1.0 / DOLLY_FACTOR.powf(deltaTime * 10.0)),
+            LEFT => self.Rotate(-self.RotationSpeed * deltaTime, 0.0),
+            RIGHT => self.Rotate(self.RotationSpeed * deltaTime, 0.0),
+            _ => {}
+        }
+    }
+
+    /// Rotates while the left button is held and pans while the middle button is held.
+    pub fn ProcessMouseMovement(&mut self, xoffset: f32, yoffset: f32, _constrainPitch: bool) {
+        if self.rotating {
+            // the model follows the mouse, so the camera moves the opposite way
+            self.Rotate(-xoffset * self.MouseSensitivity, -yoffset * self.MouseSensitivity);
+        } else if self.panning {
+            let scale = self.PanSpeed * self.Distance;
+            self.Target += self.Right * (-xoffset * scale) + self.Up * (-yoffset * scale);
+            self.updateCameraVectors();
+        }
+    }
+
+    /// Each step of the wheel moves 10% closer to/further away from the target
+    pub fn ProcessMouseScroll(&mut self, yoffset: f32) {
+        self.Dolly(DOLLY_FACTOR.powf(yoffset));
+    }
+
+    pub fn ProcessMouseButton(&mut self, button: Camera_MouseButton, pressed: bool) {
+        match button {
+            MOUSE_LEFT => self.rotating = pressed,
+            MOUSE_MIDDLE => self.panning = pressed,
+            MOUSE_RIGHT => {}
+        }
+    }
+
+    /// changes azimuth and elevation by the given degrees
+    pub fn Rotate(&mut self, azimuth: f32, elevation: f32) {
+        self.Azimuth = (self.Azimuth + azimuth) % 360.0;
+        // stay away from the poles, where the view direction would be parallel to WorldUp
+        self.Elevation = (self.Elevation + elevation).max(-89.0).min(89.0);
+        self.updateCameraVectors();
+    }
+
+    /// multiplies the distance to the target by `factor`
+    pub fn Dolly(&mut self, factor: f32) {
+        self.Distance = (self.Distance * factor).max(self.MinDistance).min(self.MaxDistance);
+        self.updateCameraVectors();
+    }
+
+    /// Calculates Position and the Front, Right and Up vectors from the orbit parameters
+    fn updateCameraVectors(&mut self) {
+        let (azimuth, elevation) = (self.Azimuth.to_radians(), self.Elevation.to_radians());
+        let offset = vec3(elevation.cos() * azimuth.sin(), elevation.sin(), elevation.cos() * azimuth.cos());
+        self.Position = self.Target + offset * self.Distance;
+        self.Front = -offset;
+        self.Right = self.Front.cross(self.WorldUp).normalize();
+        self.Up = self.Right.cross(self.Front).normalize();
+    }
+}
+
+impl CameraController for OrbitCamera {
+    fn GetViewMatrix(&self) -> Matrix4 {
+        OrbitCamera::GetViewMatrix(self)
+    }
+
+    fn ProcessKeyboard(&mut self, direction: Camera_Movement, deltaTime: f32) {
+        OrbitCamera::ProcessKeyboard(self, direction, deltaTime)
+    }
+
+    fn ProcessMouseMovement(&mut self, xoffset: f32, yoffset: f32, constrainPitch: bool) {
+        OrbitCamera::ProcessMouseMovement(self, xoffset, yoffset, constrainPitch)
+    }
+
+    fn ProcessMouseScroll(&mut self, yoffset: f32) {
+        OrbitCamera::ProcessMouseScroll(self, yoffset)
+    }
+
+    fn GetProjectionMatrix(&self) -> Matrix4 {
+        OrbitCamera::GetProjectionMatrix(self)
+    }
+
+    fn SetAspect(&mut self, aspect: f32) {
+        self.Aspect = aspect;
+    }
+
+    fn ProcessMouseButton(&mut self, button: Camera_MouseButton, pressed: bool) {
+        OrbitCamera::ProcessMouseButton(self, button, pressed)
+    }
+}
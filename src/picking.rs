@@ -0,0 +1,146 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// Ray picking: rays through a cursor position, and ray-triangle/ray-box intersection tests.
+/// Everything here works on plain positions and needs no GL context; `Mesh::Intersect` and
+/// `Model::Pick` apply it to loaded geometry. Usage:
+///
+///     let (x, y) = window.get_cursor_pos();
+///     let (width, height) = window.get_size();
+///     let ray = screen_ray(x as f32, y as f32, width as f32, height as f32, &camera.GetViewMatrix(), &camera.GetProjectionMatrix());
+///     if let Some(hit) = model.Pick(&ray, &modelMatrix) { ... }
+
+use std::f32;
+
+use cgmath;
+use cgmath::prelude::*;
+
+use bounds::Aabb;
+
+type Point3 = cgmath::Point3<f32>;
+type Vector3 = cgmath::Vector3<f32>;
+type Vector4 = cgmath::Vector4<f32>;
+type Matrix4 = cgmath::Matrix4<f32>;
+
+const EPSILON: f32 = 1e-7;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Point3,
+    /// distances along the ray are in units of its length, so normalize it for world units
+    pub direction: Vector3,
+}
+
+impl Ray {
+    pub fn new(origin: Point3, direction: Vector3) -> Ray {
+        Ray { origin, direction }
+    }
+
+    pub fn at(&self, distance: f32) -> Point3 {
+        self.origin + self.direction * distance
+    }
+
+    /// The ray transformed by `m`. The direction is not normalized again, so distances along the
+    /// transformed ray are the same as along this one.
+    pub fn transform(&self, m: &Matrix4) -> Ray {
+        Ray { origin: m.transform_point(self.origin), direction: m.transform_vector(self.direction) }
+    }
+}
+
+/// Where a ray hits a model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PickHit {
+    /// index into `Model::meshes`
+    pub mesh: usize,
+    /// index of the triangle in the mesh, i.e. its first index is `indices[3 * triangle]`
+    pub triangle: usize,
+    /// along the ray
+    pub distance: f32,
+}
+
+/// The world space ray from the camera through the cursor position `x`, `y` (in window coordinates
+/// with the origin at the top left, as reported by GLFW) in a window of `width` x `height`. The
+/// ray starts at the near plane and its direction is normalized.
+pub fn screen_ray(x: f32, y: f32, width: f32, height: f32, view: &Matrix4, projection: &Matrix4) -> Ray {
+    let inverse = (projection * view).invert().expect("view-projection matrix is not invertible");
+    let (ndcX, ndcY) = (2.0 * x / width - 1.0, 1.0 - 2.0 * y / height);
+    let unproject = |z: f32| {
+        let p = inverse * Vector4::new(ndcX, ndcY, z, 1.0);
+        Point3::from_vec(p.truncate() / p.w)
+    };
+    let (a, b) = (unproject(-1.0), unproject(1.0));
+    // The camera looks down -Z in view space. Usually depth -1 is the near plane, but with a
+    // reverse-Z projection it's depth 1 and depth -1 ends up behind the camera.
+    let depth = |p: Point3| -view.transform_point(p).z;
+    let origin = if depth(a) > 0.0 && (depth(a) <= depth(b) || depth(b) <= 0.0) { a } else { b };
+    let forward = -view.row(2).truncate();
+    let direction = (b - a).normalize();
+    Ray::new(origin, if direction.dot(forward) >= 0.0 { direction } else { -direction })
+}
+
+/// Distance to the intersection with the triangle a, b, c (either side), Möller-Trumbore
+pub fn ray_triangle(ray: &Ray, a: Point3, b: Point3, c: Point3) -> Option<f32> {
+    let (edge1, edge2) = (b - a, c - a);
+    let p = ray.direction.cross(edge2);
+    let determinant = edge1.dot(p);
+    if determinant.abs() < EPSILON {
+        return None; // the ray is parallel to the triangle
+    }
+    let inverseDeterminant = 1.0 / determinant;
+
+    // barycentric coordinates of the hit point
+    let s = ray.origin - a;
+    let u = s.dot(p) * inverseDeterminant;
+    if u < 0.0 || u > 1.0 {
+        return None;
+    }
+    let q = s.cross(edge1);
+    let v = ray.direction.dot(q) * inverseDeterminant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let distance = edge2.dot(q) * inverseDeterminant;
+    if distance > EPSILON { Some(distance) } else { None }
+}
+
+/// Distance to the point where the ray enters the box (0 if it starts inside), slab test
+pub fn ray_aabb(ray: &Ray, aabb: &Aabb) -> Option<f32> {
+    let (mut tMin, mut tMax) = (0.0f32, f32::INFINITY);
+    for axis in 0..3 {
+        let (origin, direction) = (ray.origin[axis], ray.direction[axis]);
+        let (min, max) = (aabb.min[axis], aabb.max[axis]);
+        if direction.abs() < EPSILON {
+            // parallel to the slab: either always or never inside it
+            if origin < min || origin > max {
+                return None;
+            }
+            continue;
+        }
+        let (mut t1, mut t2) = ((min - origin) / direction, (max - origin) / direction);
+        if t1 > t2 {
+            ::std::mem::swap(&mut t1, &mut t2);
+        }
+        tMin = tMin.max(t1);
+        tMax = tMax.min(t2);
+        if tMin > tMax {
+            return None;
+        }
+    }
+    Some(tMin)
+}
+
+/// The closest triangle of an indexed triangle list hit by the ray, as (triangle index, distance).
+/// `position` returns the position of a vertex index.
+pub fn ray_triangles<F: Fn(u32) -> Point3>(ray: &Ray, indices: &[u32], position: F) -> Option<(usize, f32)> {
+    indices.chunks(3)
+        .enumerate()
+        .filter(|&(_, triangle)| triangle.len() == 3)
+        .filter_map(|(i, triangle)| {
+            ray_triangle(ray, position(triangle[0]), position(triangle[1]), position(triangle[2]))
+                .map(|distance| (i, distance))
+        })
+        .fold(None, |closest: Option<(usize, f32)>, hit| match closest {
+            Some(closest) if closest.1 <= hit.1 => Some(closest),
+            _ => Some(hit),
+        })
+}
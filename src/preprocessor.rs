@@ -0,0 +1,175 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// Source-level preprocessing of shader files before they are handed to the GL compiler.
+///
+/// `#include "file.glsl"` pastes the named file (resolved relative to the including file) in place
+/// of the directive. `#line` directives are emitted around every included file, so the compiler
+/// reports errors as `<source string>:<line>` of the original file; `Source::files` maps the source
+/// string numbers back to file names.
+/// `inject_defines` adds `#define`s right after `#version`, to compile specialized variants of a shader.
+/// Only depends on `std`, so it can be used without a GL context.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// A fully preprocessed shader source
+#[derive(Debug, Clone)]
+pub struct Source {
+    pub code: String,
+    /// all files that went into `code`; the index is the source string number used in `#line`
+    pub files: Vec<String>,
+}
+
+/// Preprocessor defines, name -> value (may be empty).
+/// Ordered, so the same set always produces the same source and program cache key.
+pub type Defines = BTreeMap<String, String>;
+
+#[derive(Debug)]
+pub enum PreprocessError {
+    Io { path: String, error: io::Error },
+    /// a file (directly or indirectly) includes itself; the chain ends with the repeated file
+    IncludeCycle { chain: Vec<String> },
+    /// an `#include` that isn't followed by a quoted file name
+    InvalidInclude { file: String, line: usize },
+}
+
+impl fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PreprocessError::Io { ref path, ref error } => write!(f, "Failed to read shader {}: {}", path, error),
+            PreprocessError::IncludeCycle { ref chain } => write!(f, "#include cycle: {}", chain.join(" -> ")),
+            PreprocessError::InvalidInclude { ref file, line } =>
+                write!(f, "{}:{}: expected #include \"file\"", file, line),
+        }
+    }
+}
+
+impl Error for PreprocessError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            PreprocessError::Io { ref error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Reads `path` and resolves all `#include` directives in it.
+/// A file without includes is returned unchanged.
+pub fn preprocess(path: &str) -> Result<Source, PreprocessError> {
+    let mut source = Source { code: String::new(), files: Vec::new() };
+    let mut stack = Vec::new();
+    let code = readFile(Path::new(path), path)?;
+    expand(Path::new(path), &code, &mut stack, &mut source)?;
+    Ok(source)
+}
+
+/// Like `preprocess` for source code that doesn't come from a file; `name` is only used for
+/// error messages and includes are resolved relative to the working directory.
+pub fn preprocess_code(code: &str, name: &str) -> Result<Source, PreprocessError> {
+    let mut source = Source { code: String::new(), files: Vec::new() };
+    let mut stack = Vec::new();
+    expand(Path::new(name), code, &mut stack, &mut source)?;
+    Ok(source)
+}
+
+/// Inserts a `#define NAME VALUE` line for each define right after the `#version` directive (or
+/// at the top if there is none), followed by a `#line` so the rest of the file keeps its line numbers.
+pub fn inject_defines(source: &mut Source, defines: &Defines) {
+    if defines.is_empty() {
+        return;
+    }
+    let (offset, nextLine) = versionLine(&source.code).unwrap_or((0, 1));
+    let mut code = String::with_capacity(source.code.len() + 32 * defines.len());
+    code.push_str(&source.code[..offset]);
+    for (name, value) in defines {
+        code.push_str(format!("#define {} {}", name, value).trim_end());
+        code.push('\n');
+    }
+    code.push_str(&format!("#line {} 0\n", nextLine));
+    code.push_str(&source.code[offset..]);
+    source.code = code;
+}
+
+/// byte offset right after the `#version` line and the number of the line following it.
+/// `#version` has to come before anything but comments, so it's always in the top-level file.
+fn versionLine(code: &str) -> Option<(usize, usize)> {
+    let mut offset = 0;
+    for (i, line) in code.lines().enumerate() {
+        offset += line.len() + 1;
+        if line.trim_start().starts_with("#version") {
+            return Some((offset.min(code.len()), i + 2));
+        }
+    }
+    None
+}
+
+fn expand(path: &Path, code: &str, stack: &mut Vec<PathBuf>, source: &mut Source) -> Result<(), PreprocessError> {
+    let name = path.to_string_lossy().into_owned();
+
+    // compare canonical paths, so `a/../b.glsl` and `b.glsl` are recognized as the same file
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if stack.contains(&canonical) {
+        let mut chain: Vec<String> = stack.iter().map(|p| p.to_string_lossy().into_owned()).collect();
+        chain.push(canonical.to_string_lossy().into_owned());
+        return Err(PreprocessError::IncludeCycle { chain });
+    }
+    stack.push(canonical);
+
+    let index = source.files.len();
+    source.files.push(name.clone());
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+
+    for (i, line) in code.lines().enumerate() {
+        let lineNr = i + 1;
+        match parseInclude(line) {
+            None => {
+                source.code.push_str(line);
+                source.code.push('\n');
+            }
+            Some(Some(included)) => {
+                let includedPath = directory.join(included);
+                let includedCode = readFile(&includedPath, &includedPath.to_string_lossy())?;
+                source.code.push_str(&format!("#line 1 {}\n", source.files.len()));
+                expand(&includedPath, &includedCode, stack, source)?;
+                // continue with the line after the #include
+                source.code.push_str(&format!("#line {} {}\n", lineNr + 1, index));
+            }
+            Some(None) => return Err(PreprocessError::InvalidInclude { file: name, line: lineNr }),
+        }
+    }
+
+    stack.pop();
+    Ok(())
+}
+
+/// `None` if the line isn't an include directive, `Some(None)` if it is one but malformed
+fn parseInclude(line: &str) -> Option<Option<&str>> {
+    let line = line.trim_start();
+    if !line.starts_with('#') {
+        return None;
+    }
+    let directive = line[1..].trim_start();
+    if !directive.starts_with("include") {
+        return None;
+    }
+    let argument = directive["include".len()..].trim();
+    if argument.len() >= 2 && argument.starts_with('"') && argument[1..].contains('"') {
+        let end = argument[1..].find('"').unwrap() + 1;
+        Some(Some(&argument[1..end]))
+    } else {
+        Some(None)
+    }
+}
+
+fn readFile(path: &Path, name: &str) -> Result<String, PreprocessError> {
+    let mut code = String::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_string(&mut code))
+        .map_err(|error| PreprocessError::Io { path: name.to_string(), error })?;
+    Ok(code)
+}
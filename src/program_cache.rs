@@ -0,0 +1,139 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// On-disk cache of linked program binaries (`glGetProgramBinary`/`glProgramBinary`, GL 4.1 or
+/// ARB_get_program_binary), so programs don't have to be recompiled on every launch.
+///
+/// Entries are keyed on a hash of the preprocessed source of every stage and the GL
+/// vendor/renderer/version strings, so editing a shader or updating the driver simply misses the
+/// cache. If the driver rejects a cached binary anyway, the program is compiled from source and
+/// the entry is overwritten.
+///
+/// `Shader::new` and friends use the directory in the `SHADER_CACHE_DIR` environment variable if it
+/// is set; `ShaderBuilder::cache` takes an explicit cache.
+
+use std::env;
+use std::ffi::CStr;
+use std::fs;
+use std::io;
+use std::os::raw::{c_char, c_void};
+use std::path::PathBuf;
+
+use gl;
+use gl::types::*;
+
+pub const CACHE_DIR_VARIABLE: &str = "SHADER_CACHE_DIR";
+
+#[derive(Debug, Clone)]
+pub struct ProgramCache {
+    pub directory: PathBuf,
+}
+
+impl ProgramCache {
+    pub fn new<P: Into<PathBuf>>(directory: P) -> ProgramCache {
+        ProgramCache { directory: directory.into() }
+    }
+
+    /// `None` if `SHADER_CACHE_DIR` isn't set
+    pub fn from_env() -> Option<ProgramCache> {
+        env::var_os(CACHE_DIR_VARIABLE).map(ProgramCache::new)
+    }
+
+    /// whether the driver supports at least one binary format
+    pub unsafe fn supported() -> bool {
+        let mut formats = 0;
+        gl::GetIntegerv(gl::NUM_PROGRAM_BINARY_FORMATS, &mut formats);
+        formats > 0
+    }
+
+    /// Hashes the given (stage name, source code) pairs together with the GL driver strings.
+    pub unsafe fn key(&self, sources: &[(&str, &str)]) -> u64 {
+        let mut hash = Fnv1a::new();
+        for &name in &[gl::VENDOR, gl::RENDERER, gl::VERSION] {
+            hash.write(glString(name).as_bytes());
+        }
+        for &(stage, code) in sources {
+            hash.write(stage.as_bytes());
+            hash.write(code.as_bytes());
+        }
+        hash.finish()
+    }
+
+    fn path(&self, key: u64) -> PathBuf {
+        self.directory.join(format!("{:016x}.bin", key))
+    }
+
+    /// Creates a program from the cached binary. `None` if there is no entry or the driver rejects it.
+    pub unsafe fn load(&self, key: u64) -> Option<u32> {
+        let data = fs::read(self.path(key)).ok()?;
+        if data.len() <= 4 {
+            return None;
+        }
+        // file layout: binary format (u32, little endian), followed by the binary itself
+        let format = u32::from(data[0]) | u32::from(data[1]) << 8 | u32::from(data[2]) << 16 | u32::from(data[3]) << 24;
+        let binary = &data[4..];
+
+        let program = gl::CreateProgram();
+        gl::ProgramBinary(program, format, binary.as_ptr() as *const c_void, binary.len() as GLsizei);
+        let mut success = gl::FALSE as GLint;
+        gl::GetProgramiv(program, gl::LINK_STATUS, &mut success);
+        if success != gl::TRUE as GLint {
+            gl::DeleteProgram(program);
+            return None;
+        }
+        Some(program)
+    }
+
+    /// Writes the binary of a linked program to the cache.
+    /// The program should have been linked with `PROGRAM_BINARY_RETRIEVABLE_HINT` set.
+    pub unsafe fn store(&self, key: u64, program: u32) -> io::Result<()> {
+        let mut length = 0;
+        gl::GetProgramiv(program, gl::PROGRAM_BINARY_LENGTH, &mut length);
+        if length <= 0 {
+            return Err(io::Error::new(io::ErrorKind::Other, "driver returned an empty program binary"));
+        }
+
+        let mut binary = vec![0u8; length as usize];
+        let (mut written, mut format) = (0, 0);
+        gl::GetProgramBinary(program, length, &mut written, &mut format, binary.as_mut_ptr() as *mut c_void);
+        binary.truncate(written as usize);
+
+        let mut data = Vec::with_capacity(4 + binary.len());
+        data.extend_from_slice(&[format as u8, (format >> 8) as u8, (format >> 16) as u8, (format >> 24) as u8]);
+        data.extend_from_slice(&binary);
+
+        fs::create_dir_all(&self.directory)?;
+        fs::write(self.path(key), data)
+    }
+}
+
+unsafe fn glString(name: GLenum) -> String {
+    let string = gl::GetString(name);
+    if string.is_null() {
+        return String::new();
+    }
+    CStr::from_ptr(string as *const c_char).to_string_lossy().into_owned()
+}
+
+/// 64 bit FNV-1a; unlike `DefaultHasher` its output is guaranteed to stay the same across Rust
+/// versions, which matters for keys stored on disk.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Fnv1a {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+        // separator, so ("ab", "c") and ("a", "bc") hash differently
+        self.0 ^= 0xff;
+        self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
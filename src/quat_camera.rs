@@ -0,0 +1,237 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// A free-flying camera whose orientation is a quaternion instead of yaw/pitch Euler angles:
+/// there is no gimbal lock, so it can look straight up/down, loop around and roll. Mouse movement
+/// and roll rotate around the camera's own axes, like an aircraft.
+///
+/// It has the same fields (`Position`, `Front`, `Up`, `Right`, `Zoom`, ...) and methods as `Camera`,
+/// so it can be swapped in for it, e.g.
+///
+///     let mut camera = QuatCamera::new(Point3::new(0.0, 0.0, 3.0));
+
+use cgmath;
+use cgmath::{Deg, Matrix3, Quaternion};
+use cgmath::prelude::*;
+
+use bookmarks::Bookmark;
+use camera::{Projection, Camera, CameraController, Camera_Movement, ASPECT};
+use camera::Camera_Movement::*;
+
+type Point3 = cgmath::Point3<f32>;
+type Vector3 = cgmath::Vector3<f32>;
+type Matrix4 = cgmath::Matrix4<f32>;
+
+// Default camera values
+const SPEED: f32 = 2.5;
+const SENSITIVTY: f32 = 0.1;
+const ROLL_SPEED: f32 = 90.0;
+const ZOOM: f32 = 45.0;
+
+pub struct QuatCamera {
+    // Camera Attributes
+    pub Position: Point3,
+    /// rotates camera space (looking down -Z with +Y up) into world space
+    pub Orientation: Quaternion<f32>,
+    // derived from Orientation, kept up to date by all methods that change it
+    pub Front: Vector3,
+    pub Up: Vector3,
+    pub Right: Vector3,
+    // Camera options
+    pub MovementSpeed: f32,
+    pub MouseSensitivity: f32,
+    /// MovementSpeed multiplier, set by `SetSpeedModifier`
+    pub SpeedModifier: f32,
+    /// degrees per second while ROLL_LEFT/ROLL_RIGHT is held
+    pub RollSpeed: f32,
+    pub Zoom: f32,
+    // Projection
+    /// width / height of the viewport
+    pub Aspect: f32,
+    pub Projection: Projection,
+}
+
+impl Default for QuatCamera {
+    /// looks down -Z, like the default `Camera`
+    fn default() -> QuatCamera {
+        let mut camera = QuatCamera {
+            Position: Point3::new(0.0, 0.0, 0.0),
+            Orientation: Quaternion::one(),
+            Front: Vector3::zero(), // initialized later
+            Up: Vector3::zero(), // initialized later
+            Right: Vector3::zero(), // initialized later
+            MovementSpeed: SPEED,
+            MouseSensitivity: SENSITIVTY,
+            SpeedModifier: 1.0,
+            RollSpeed: ROLL_SPEED,
+            Zoom: ZOOM,
+            Aspect: ASPECT,
+            Projection: Projection::default(),
+        };
+        camera.updateCameraVectors();
+        camera
+    }
+}
+
+impl<'a> From<&'a Camera> for QuatCamera {
+    /// takes over position, view direction and options of an Euler angle camera
+    fn from(camera: &'a Camera) -> QuatCamera {
+        let mut quatCamera = QuatCamera {
+            Position: camera.Position,
+            MovementSpeed: camera.MovementSpeed,
+            MouseSensitivity: camera.MouseSensitivity,
+            Zoom: camera.Zoom,
+            Aspect: camera.Aspect,
+            Projection: camera.Projection,
+            ..QuatCamera::default()
+        };
+        quatCamera.SetOrientation(camera.Front, camera.Up);
+        quatCamera
+    }
+}
+
+impl QuatCamera {
+    pub fn new(position: Point3) -> QuatCamera {
+        QuatCamera { Position: position, ..QuatCamera::default() }
+    }
+
+    /// Returns the view matrix, exactly like `Camera::GetViewMatrix`
+    pub fn GetViewMatrix(&self) -> Matrix4 {
+        Matrix4::look_at(self.Position, self.Position + self.Front, self.Up)
+    }
+
+    /// Returns the projection matrix for the current field of view (`Zoom`) and aspect ratio
+    pub fn GetProjectionMatrix(&self) -> Matrix4 {
+        self.Projection.matrix(self.Zoom, self.Aspect)
+    }
+
+    /// Orients the camera to look along `front`; `up` only needs to be roughly perpendicular to it.
+    pub fn SetOrientation(&mut self, front: Vector3, up: Vector3) {
+        let front = front.normalize();
+        let right = front.cross(up).normalize();
+        let up = right.cross(front);
+        // the columns are the world space directions of the camera's x, y and z axes
+        self.Orientation = Quaternion::from(Matrix3::from_cols(right, up, -front)).normalize();
+        self.updateCameraVectors();
+    }
+
+    /// Turns the camera to look at `target`, keeping the current up vector as far as possible
+    pub fn LookAt(&mut self, target: Point3) {
+        let up = self.Up;
+        self.SetOrientation(target - self.Position, up);
+    }
+
+    /// The view direction as yaw/pitch like `Camera`'s; roll isn't part of a bookmark.
+    pub fn GetBookmark(&self) -> Bookmark {
+        Bookmark {
+            position: self.Position,
+            yaw: self.Front.z.atan2(self.Front.x).to_degrees(),
+            pitch: self.Front.y.max(-1.0).min(1.0).asin().to_degrees(),
+            zoom: self.Zoom,
+            speed: self.MovementSpeed,
+        }
+    }
+
+    /// Restores a bookmark, upright (without roll)
+    pub fn SetBookmark(&mut self, bookmark: &Bookmark) {
+        // looking straight up or down, "upright" isn't defined
+        let (yaw, pitch) = (bookmark.yaw.to_radians(), bookmark.pitch.max(-89.0).min(89.0).to_radians());
+        let front = Vector3::new(yaw.cos() * pitch.cos(), pitch.sin(), yaw.sin() * pitch.cos());
+        self.Position = bookmark.position;
+        self.Zoom = bookmark.zoom;
+        self.MovementSpeed = bookmark.speed;
+        self.SetOrientation(front, Vector3::unit_y());
+    }
+
+    /// Processes input received from any keyboard-like input system. Accepts input parameter in the form of camera defined ENUM (to abstract it from windowing systems)
+    pub fn ProcessKeyboard(&mut self, direction: Camera_Movement, deltaTime: f32) {
+        let velocity = self.MovementSpeed * self.SpeedModifier * deltaTime;
+        match direction {
+            FORWARD => self.Position += self.Front * velocity,
+            BACKWARD => self.Position += -(self.Front * velocity),
+            LEFT => self.Position += -(self.Right * velocity),
+            RIGHT => self.Position += self.Right * velocity,
+            UP => self.Position += self.Up * velocity,
+            DOWN => self.Position += -(self.Up * velocity),
+            // rolling left raises the right side: a positive rotation around the camera's z axis
+            ROLL_LEFT => self.rotateLocal(Vector3::unit_z(), self.RollSpeed * deltaTime),
+            ROLL_RIGHT => self.rotateLocal(Vector3::unit_z(), -self.RollSpeed * deltaTime),
+        }
+    }
+
+    /// Processes input received from a mouse input system. Expects the offset value in both the x and y direction.
+    /// Yaw and pitch are relative to the current orientation, so there are no singularities
+    /// and `constrainPitch` is ignored.
+    pub fn ProcessMouseMovement(&mut self, xoffset: f32, yoffset: f32, _constrainPitch: bool) {
+        // yaw around the camera's up axis, then pitch around its (new) right axis
+        self.rotateLocal(Vector3::unit_y(), -xoffset * self.MouseSensitivity);
+        self.rotateLocal(Vector3::unit_x(), yoffset * self.MouseSensitivity);
+    }
+
+    // Processes input received from a mouse scroll-wheel event. Only requires input on the vertical wheel-axis
+    pub fn ProcessMouseScroll(&mut self, yoffset: f32) {
+        self.Zoom = (self.Zoom - yoffset).max(1.0).min(45.0);
+    }
+
+    /// rotates around an axis given in camera space
+    fn rotateLocal(&mut self, axis: Vector3, degrees: f32) {
+        let rotation = Quaternion::from_axis_angle(axis, Deg(degrees));
+        // re-normalize, so rounding errors don't accumulate into a scale over many frames
+        self.Orientation = (self.Orientation * rotation).normalize();
+        self.updateCameraVectors();
+    }
+
+    /// Calculates the Front, Right and Up vectors from the (updated) orientation
+    fn updateCameraVectors(&mut self) {
+        self.Front = self.Orientation * -Vector3::unit_z();
+        self.Right = self.Orientation * Vector3::unit_x();
+        self.Up = self.Orientation * Vector3::unit_y();
+    }
+}
+
+impl CameraController for QuatCamera {
+    fn GetViewMatrix(&self) -> Matrix4 {
+        QuatCamera::GetViewMatrix(self)
+    }
+
+    fn ProcessKeyboard(&mut self, direction: Camera_Movement, deltaTime: f32) {
+        QuatCamera::ProcessKeyboard(self, direction, deltaTime)
+    }
+
+    fn ProcessMouseMovement(&mut self, xoffset: f32, yoffset: f32, constrainPitch: bool) {
+        QuatCamera::ProcessMouseMovement(self, xoffset, yoffset, constrainPitch)
+    }
+
+    fn ProcessMouseScroll(&mut self, yoffset: f32) {
+        QuatCamera::ProcessMouseScroll(self, yoffset)
+    }
+
+    fn GetProjectionMatrix(&self) -> Matrix4 {
+        QuatCamera::GetProjectionMatrix(self)
+    }
+
+    fn SetAspect(&mut self, aspect: f32) {
+        self.Aspect = aspect;
+    }
+
+    fn SetSpeedModifier(&mut self, modifier: f32) {
+        self.SpeedModifier = modifier;
+    }
+
+    fn GetPose(&self) -> Option<(Point3, Quaternion<f32>)> {
+        Some((self.Position, self.Orientation))
+    }
+
+    fn SetPose(&mut self, position: Point3, orientation: Quaternion<f32>) {
+        self.Position = position;
+        self.Orientation = orientation.normalize();
+        self.updateCameraVectors();
+    }
+
+    fn GetBookmark(&self) -> Option<Bookmark> {
+        Some(QuatCamera::GetBookmark(self))
+    }
+
+    fn SetBookmark(&mut self, bookmark: &Bookmark) {
+        QuatCamera::SetBookmark(self, bookmark)
+    }
+}
@@ -1,68 +1,281 @@
 #![allow(non_snake_case)]
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
 use std::ffi::{CString, CStr};
-use std::fs::File;
-use std::io::Read;
+use std::fmt;
+use std::io;
 use std::ptr;
-use std::str;
 
 use gl;
 use gl::types::*;
 
-use cgmath::{Matrix, Matrix4, Vector3};
-use cgmath::prelude::*;
+use cgmath::{Matrix4, Vector3, vec3};
+
+use preprocessor;
+use preprocessor::{Defines, PreprocessError, Source};
+use program_cache::ProgramCache;
+use uniform;
+use uniform::{IntoUniform, UniformInfo};
 
 pub struct Shader {
     pub ID: u32,
+    /// all active uniforms, queried once after linking
+    pub uniforms: HashMap<String, UniformInfo>,
+    /// opt-in: report setting uniforms that aren't active in the program or have a different type.
+    /// (unused uniforms are removed by the GLSL compiler, so this isn't on by default)
+    pub strict: bool,
+    /// names already reported in strict mode, so the render loop doesn't flood the console
+    reported: RefCell<HashSet<String>>,
+}
+
+/// The programmable pipeline stages a `Shader` can be built from.
+/// Tessellation needs GL 4.0 and compute GL 4.3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    TessControl,
+    TessEvaluation,
+    Geometry,
+    Fragment,
+    Compute,
+}
+
+impl ShaderStage {
+    fn glType(self) -> GLenum {
+        match self {
+            ShaderStage::Vertex => gl::VERTEX_SHADER,
+            ShaderStage::TessControl => gl::TESS_CONTROL_SHADER,
+            ShaderStage::TessEvaluation => gl::TESS_EVALUATION_SHADER,
+            ShaderStage::Geometry => gl::GEOMETRY_SHADER,
+            ShaderStage::Fragment => gl::FRAGMENT_SHADER,
+            ShaderStage::Compute => gl::COMPUTE_SHADER,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ShaderStage::Vertex => "VERTEX",
+            ShaderStage::TessControl => "TESS_CONTROL",
+            ShaderStage::TessEvaluation => "TESS_EVALUATION",
+            ShaderStage::Geometry => "GEOMETRY",
+            ShaderStage::Fragment => "FRAGMENT",
+            ShaderStage::Compute => "COMPUTE",
+        }
+    }
+}
+
+impl fmt::Display for ShaderStage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A single message from a compile or link info log.
+/// `line` is `None` if the driver's message didn't have a recognizable location.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub file: String,
+    pub line: Option<u32>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file, line, self.message),
+            None if self.file.is_empty() => write!(f, "{}", self.message),
+            None => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
+}
+
+/// The raw info log of a shader or program, plus the diagnostics parsed from it.
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct InfoLog {
+    pub raw: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl InfoLog {
+    /// Parses the info log formats of the common drivers:
+    ///   Mesa/Intel:  `0:12(5): error: ...`
+    ///   NVIDIA:      `0(12) : error C0000: ...`
+    ///   AMD/Apple:   `ERROR: 0:12: ...`
+    /// The leading number is the index of the source string, which is mapped to a name via `files`.
+    /// Lines that don't match any format are kept as diagnostics without a line number, as are
+    /// all lines if there are no `files` (e.g. link logs).
+    pub fn parse(raw: &str, files: &[&str]) -> InfoLog {
+        let fallback = files.first().cloned().unwrap_or("");
+        let diagnostics = raw.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| match parseLocation(line).filter(|_| !files.is_empty()) {
+                Some((source, lineNr, message)) => Diagnostic {
+                    file: files.get(source as usize).cloned().unwrap_or(fallback).to_string(),
+                    line: Some(lineNr),
+                    message,
+                },
+                None => Diagnostic { file: fallback.to_string(), line: None, message: line.to_string() },
+            })
+            .collect();
+
+        InfoLog { raw: raw.to_string(), diagnostics }
+    }
+}
+
+impl fmt::Display for InfoLog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for diagnostic in &self.diagnostics {
+            writeln!(f, "{}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits a single info log line into (source string index, line number, message)
+fn parseLocation(line: &str) -> Option<(u32, u32, String)> {
+    // AMD/Apple put the severity in front of the location, the others after it
+    let (severity, rest) = if line.starts_with("ERROR: ") {
+        ("error: ", &line[7..])
+    } else if line.starts_with("WARNING: ") {
+        ("warning: ", &line[9..])
+    } else {
+        ("", line)
+    };
+
+    let (source, rest) = splitNumber(rest)?;
+    let (lineNr, rest) = if rest.starts_with('(') {
+        // NVIDIA: `0(12) : ...`
+        let (lineNr, rest) = splitNumber(&rest[1..])?;
+        if !rest.starts_with(')') {
+            return None;
+        }
+        (lineNr, &rest[1..])
+    } else if rest.starts_with(':') {
+        // Mesa/AMD: `0:12(5): ...` or `0:12: ...`
+        let (lineNr, mut rest) = splitNumber(&rest[1..])?;
+        if rest.starts_with('(') {
+            rest = &rest[rest.find(')')? + 1..];
+        }
+        (lineNr, rest)
+    } else {
+        return None;
+    };
+
+    let rest = rest.trim_start();
+    if !rest.starts_with(':') {
+        return None;
+    }
+    Some((source, lineNr, format!("{}{}", severity, rest[1..].trim())))
+}
+
+fn splitNumber(s: &str) -> Option<(u32, &str)> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or_else(|| s.len());
+    let number = s[..end].parse().ok()?;
+    Some((number, &s[end..]))
+}
+
+#[derive(Debug)]
+pub enum ShaderError {
+    /// a shader source file couldn't be read
+    Io { path: String, error: io::Error },
+    /// an `#include` couldn't be resolved
+    Preprocess(PreprocessError),
+    /// a single stage failed to compile
+    Compile { stage: ShaderStage, path: String, log: InfoLog },
+    /// the compiled stages failed to link into a program
+    Link { log: InfoLog },
+    /// the combination of stages passed to `ShaderBuilder` can't form a program
+    InvalidStages(String),
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ShaderError::Io { ref path, ref error } =>
+                write!(f, "Failed to read shader {}: {}", path, error),
+            ShaderError::Preprocess(ref error) => write!(f, "{}", error),
+            ShaderError::Compile { stage, ref path, ref log } =>
+                write!(f, "ERROR::SHADER_COMPILATION_ERROR of type: {} ({})\n{}", stage, path, log),
+            ShaderError::Link { ref log } =>
+                write!(f, "ERROR::PROGRAM_LINKING_ERROR\n{}", log),
+            ShaderError::InvalidStages(ref message) => write!(f, "Invalid shader stages: {}", message),
+        }
+    }
+}
+
+impl Error for ShaderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            ShaderError::Io { ref error, .. } => Some(error),
+            ShaderError::Preprocess(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<PreprocessError> for ShaderError {
+    fn from(error: PreprocessError) -> ShaderError {
+        match error {
+            PreprocessError::Io { path, error } => ShaderError::Io { path, error },
+            error => ShaderError::Preprocess(error),
+        }
+    }
 }
 
 /// NOTE: mixture of `shader_s.h` and `shader_m.h` (the latter just contains
 /// a few more setters for uniforms)
 #[allow(dead_code)]
 impl Shader {
+    /// Panics if a file can't be read or a stage fails to compile/link; use `try_new` to handle errors.
     pub fn new(vertexPath: &str, fragmentPath: &str) -> Shader {
-        let mut shader = Shader { ID: 0 };
-        // 1. retrieve the vertex/fragment source code from filesystem
-        let mut vShaderFile = File::open(vertexPath)
-            .unwrap_or_else(|_| panic!("Failed to open {}", vertexPath));
-        let mut fShaderFile = File::open(fragmentPath)
-            .unwrap_or_else(|_| panic!("Failed to open {}", fragmentPath));
-        let mut vertexCode = String::new();
-        let mut fragmentCode = String::new();
-        vShaderFile
-            .read_to_string(&mut vertexCode)
-            .expect("Failed to read vertex shader");
-        fShaderFile
-            .read_to_string(&mut fragmentCode)
-            .expect("Failed to read fragment shader");
-
-        let vShaderCode = CString::new(vertexCode.as_bytes()).unwrap();
-        let fShaderCode = CString::new(fragmentCode.as_bytes()).unwrap();
-
-        // 2. compile shaders
-        unsafe {
-            // vertex shader
-            let vertex = gl::CreateShader(gl::VERTEX_SHADER);
-            gl::ShaderSource(vertex, 1, &vShaderCode.as_ptr(), ptr::null());
-            gl::CompileShader(vertex);
-            shader.checkCompileErrors(vertex, "VERTEX");
-            // fragment Shader
-            let fragment = gl::CreateShader(gl::FRAGMENT_SHADER);
-            gl::ShaderSource(fragment, 1, &fShaderCode.as_ptr(), ptr::null());
-            gl::CompileShader(fragment);
-            shader.checkCompileErrors(fragment, "FRAGMENT");
-            // shader Program
-            let ID = gl::CreateProgram();
-            gl::AttachShader(ID, vertex);
-            gl::AttachShader(ID, fragment);
-            gl::LinkProgram(ID);
-            shader.checkCompileErrors(ID, "PROGRAM");
-            // delete the shaders as they're linked into our program now and no longer necessary
-            gl::DeleteShader(vertex);
-            gl::DeleteShader(fragment);
-            shader.ID = ID;
+        Shader::try_new(vertexPath, fragmentPath).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_new(vertexPath: &str, fragmentPath: &str) -> Result<Shader, ShaderError> {
+        ShaderBuilder::new()
+            .vertex_file(vertexPath)
+            .fragment_file(fragmentPath)
+            .env_cache()
+            .build()
+    }
+
+    /// Only used in 4.9 Geometry shaders - ignore until then (shader.h in original C++)
+    pub fn with_geometry_shader(vertexPath: &str, fragmentPath: &str, geometryPath: &str) -> Self {
+        Shader::try_with_geometry_shader(vertexPath, fragmentPath, geometryPath)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_with_geometry_shader(vertexPath: &str, fragmentPath: &str, geometryPath: &str)
+        -> Result<Shader, ShaderError>
+    {
+        ShaderBuilder::new()
+            .vertex_file(vertexPath)
+            .fragment_file(fragmentPath)
+            .geometry_file(geometryPath)
+            .env_cache()
+            .build()
+    }
+
+    /// Only for compute programs: runs `x * y * z` work groups and then waits for the writes
+    /// selected by `barriers` (e.g. `gl::SHADER_IMAGE_ACCESS_BARRIER_BIT`, 0 for none) to become
+    /// visible to subsequent GL commands.
+    pub unsafe fn dispatch(&self, x: u32, y: u32, z: u32, barriers: GLbitfield) {
+        gl::UseProgram(self.ID);
+        gl::DispatchCompute(x, y, z);
+        if barriers != 0 {
+            gl::MemoryBarrier(barriers);
         }
+    }
 
-        shader
+    /// Only for compute programs: the `local_size_x/y/z` declared in the shader, e.g. to calculate
+    /// how many work groups are needed to cover an image.
+    pub unsafe fn work_group_size(&self) -> [i32; 3] {
+        let mut size = [0; 3];
+        gl::GetProgramiv(self.ID, gl::COMPUTE_WORK_GROUP_SIZE, size.as_mut_ptr());
+        size
     }
 
     /// activate the shader
@@ -71,120 +284,316 @@ impl Shader {
         gl::UseProgram(self.ID)
     }
 
+    /// Sets a uniform from any `IntoUniform` value: scalars, cgmath vectors/points/matrices
+    /// and slices of those for uniform arrays.
+    /// ------------------------------------------------------------------------
+    pub unsafe fn set<T: IntoUniform>(&self, name: &CStr, value: T) {
+        let location = self.location(name, &value);
+        if location != -1 {
+            value.upload(location);
+        }
+    }
+
     /// utility uniform functions
     /// ------------------------------------------------------------------------
     pub unsafe fn setBool(&self, name: &CStr, value: bool) {
-        gl::Uniform1i(gl::GetUniformLocation(self.ID, name.as_ptr()), value as i32);
+        self.set(name, value);
     }
     /// ------------------------------------------------------------------------
     pub unsafe fn setInt(&self, name: &CStr, value: i32) {
-        gl::Uniform1i(gl::GetUniformLocation(self.ID, name.as_ptr()), value);
+        self.set(name, value);
     }
     /// ------------------------------------------------------------------------
     pub unsafe fn setFloat(&self, name: &CStr, value: f32) {
-        gl::Uniform1f(gl::GetUniformLocation(self.ID, name.as_ptr()), value);
+        self.set(name, value);
     }
     /// ------------------------------------------------------------------------
     pub unsafe fn setVector3(&self, name: &CStr, value: &Vector3<f32>) {
-        gl::Uniform3fv(gl::GetUniformLocation(self.ID, name.as_ptr()), 1, value.as_ptr());
+        self.set(name, *value);
     }
     /// ------------------------------------------------------------------------
     pub unsafe fn setVec3(&self, name: &CStr, x: f32, y: f32, z: f32) {
-        gl::Uniform3f(gl::GetUniformLocation(self.ID, name.as_ptr()), x, y, z);
+        self.set(name, vec3(x, y, z));
     }
     /// ------------------------------------------------------------------------
     pub unsafe fn setMat4(&self, name: &CStr, mat: &Matrix4<f32>) {
-        gl::UniformMatrix4fv(gl::GetUniformLocation(self.ID, name.as_ptr()), 1, gl::FALSE, mat.as_ptr());
+        self.set(name, *mat);
     }
 
-    /// utility function for checking shader compilation/linking errors.
-    /// ------------------------------------------------------------------------
-    unsafe fn checkCompileErrors(&self, shader: u32, type_: &str) {
-        let mut success = gl::FALSE as GLint;
-        let mut infoLog = Vec::with_capacity(1024);
-        infoLog.set_len(1024 - 1); // subtract 1 to skip the trailing null character
-        if type_ != "PROGRAM" {
-            gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
-            if success != gl::TRUE as GLint {
-                gl::GetShaderInfoLog(shader, 1024, ptr::null_mut(), infoLog.as_mut_ptr() as *mut GLchar);
-                println!("ERROR::SHADER_COMPILATION_ERROR of type: {}\n{}\n \
-                          -- --------------------------------------------------- -- ",
-                         type_,
-                         str::from_utf8(&infoLog).unwrap());
+    /// Looks up the location of a uniform in the reflection table; -1 (ignored by GL) if it isn't active.
+    /// In strict mode, unknown names, mismatching types and too long arrays are reported once per name.
+    fn location<T: IntoUniform>(&self, name: &CStr, value: &T) -> GLint {
+        let name = name.to_string_lossy();
+        match self.uniforms.get(name.as_ref()) {
+            Some(info) => {
+                if self.strict && !value.accepts(info.type_) {
+                    self.report(&name, || format!(
+                        "uniform '{}' is declared as {} but set as {}",
+                        name, uniform::typeName(info.type_), value.glslType()));
+                } else if self.strict && value.count() > info.size as usize {
+                    self.report(&name, || format!(
+                        "uniform '{}' has room for {} elements but was set with {}", name, info.size, value.count()));
+                }
+                info.location
             }
-
-        } else {
-            gl::GetProgramiv(shader, gl::LINK_STATUS, &mut success);
-            if success != gl::TRUE as GLint {
-                gl::GetProgramInfoLog(shader, 1024, ptr::null_mut(), infoLog.as_mut_ptr() as *mut GLchar);
-                println!("ERROR::PROGRAM_LINKING_ERROR of type: {}\n{}\n \
-                          -- --------------------------------------------------- -- ",
-                         type_,
-                         str::from_utf8(&infoLog).unwrap());
+            None => {
+                if self.strict {
+                    self.report(&name, || format!("uniform '{}' is not an active uniform of program {}", name, self.ID));
+                }
+                -1
             }
         }
+    }
 
+    fn report<F: FnOnce() -> String>(&self, name: &str, message: F) {
+        if self.reported.borrow_mut().insert(name.to_string()) {
+            println!("WARNING::SHADER: {}", message());
+        }
     }
 
-    /// Only used in 4.9 Geometry shaders - ignore until then (shader.h in original C++)
-    pub fn with_geometry_shader(vertexPath: &str, fragmentPath: &str, geometryPath: &str) -> Self {
-        let mut shader = Shader { ID: 0 };
-        // 1. retrieve the vertex/fragment source code from filesystem
-        let mut vShaderFile = File::open(vertexPath)
-            .unwrap_or_else(|_| panic!("Failed to open {}", vertexPath));
-        let mut fShaderFile = File::open(fragmentPath)
-            .unwrap_or_else(|_| panic!("Failed to open {}", fragmentPath));
-        let mut gShaderFile = File::open(geometryPath)
-            .unwrap_or_else(|_| panic!("Failed to open {}", geometryPath));
-        let mut vertexCode = String::new();
-        let mut fragmentCode = String::new();
-        let mut geometryCode = String::new();
-        vShaderFile
-            .read_to_string(&mut vertexCode)
-            .expect("Failed to read vertex shader");
-        fShaderFile
-            .read_to_string(&mut fragmentCode)
-            .expect("Failed to read fragment shader");
-        gShaderFile
-            .read_to_string(&mut geometryCode)
-            .expect("Failed to read geometry shader");
-
-        let vShaderCode = CString::new(vertexCode.as_bytes()).unwrap();
-        let fShaderCode = CString::new(fragmentCode.as_bytes()).unwrap();
-        let gShaderCode = CString::new(geometryCode.as_bytes()).unwrap();
-
-        // 2. compile shaders
+    /// wraps a successfully linked program
+    unsafe fn fromProgram(ID: u32) -> Shader {
+        Shader {
+            ID,
+            uniforms: uniform::activeUniforms(ID),
+            strict: false,
+            reported: RefCell::new(HashSet::new()),
+        }
+    }
+
+    /// reads, compiles and links all given stages into a new program
+    pub fn from_files(stages: &[(ShaderStage, &str)]) -> Result<Shader, ShaderError> {
+        stages.iter()
+            .fold(ShaderBuilder::new(), |builder, &(stage, path)| builder.file(stage, path))
+            .env_cache()
+            .build()
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Clone)]
+enum StageSource {
+    File(String),
+    Code { name: String, code: String },
+}
+
+/// Builds a program from any combination of stages, each read from a file or given as a string:
+///
+///     let shader = ShaderBuilder::new()
+///         .vertex_file("src/_4_advanced_opengl/shaders/9.1.geometry_shader.vs")
+///         .geometry_file("src/_4_advanced_opengl/shaders/9.1.geometry_shader.gs")
+///         .fragment_file("src/_4_advanced_opengl/shaders/9.1.geometry_shader.fs")
+///         .build()?;
+///
+/// A compute program consists of a single compute stage.
+/// `define` injects preprocessor defines into every stage, to build specialized variants of the
+/// same source (see also `ShaderPermutations`).
+#[derive(Default, Clone)]
+pub struct ShaderBuilder {
+    stages: Vec<(ShaderStage, StageSource)>,
+    defines: Defines,
+    cache: Option<ProgramCache>,
+}
+
+#[allow(dead_code)]
+impl ShaderBuilder {
+    pub fn new() -> ShaderBuilder {
+        ShaderBuilder::default()
+    }
+
+    pub fn file(mut self, stage: ShaderStage, path: &str) -> ShaderBuilder {
+        self.stages.push((stage, StageSource::File(path.to_string())));
+        self
+    }
+
+    /// `name` is only used in error messages
+    pub fn source(mut self, stage: ShaderStage, name: &str, code: &str) -> ShaderBuilder {
+        self.stages.push((stage, StageSource::Code { name: name.to_string(), code: code.to_string() }));
+        self
+    }
+
+    pub fn vertex_file(self, path: &str) -> ShaderBuilder { self.file(ShaderStage::Vertex, path) }
+    pub fn tess_control_file(self, path: &str) -> ShaderBuilder { self.file(ShaderStage::TessControl, path) }
+    pub fn tess_evaluation_file(self, path: &str) -> ShaderBuilder { self.file(ShaderStage::TessEvaluation, path) }
+    pub fn geometry_file(self, path: &str) -> ShaderBuilder { self.file(ShaderStage::Geometry, path) }
+    pub fn fragment_file(self, path: &str) -> ShaderBuilder { self.file(ShaderStage::Fragment, path) }
+    pub fn compute_file(self, path: &str) -> ShaderBuilder { self.file(ShaderStage::Compute, path) }
+
+    /// `#define name value` in every stage; an empty value just defines the name, for `#ifdef`
+    pub fn define(mut self, name: &str, value: &str) -> ShaderBuilder {
+        self.defines.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// adds all of `defines`, replacing the values of names that are already defined
+    pub fn defines(mut self, defines: &Defines) -> ShaderBuilder {
+        self.defines.extend(defines.iter().map(|(name, value)| (name.clone(), value.clone())));
+        self
+    }
+
+    /// load the linked program from / store it in an on-disk binary cache
+    pub fn cache(mut self, cache: &ProgramCache) -> ShaderBuilder {
+        self.cache = Some(cache.clone());
+        self
+    }
+
+    /// use the cache configured by the `SHADER_CACHE_DIR` environment variable, if any
+    pub fn env_cache(mut self) -> ShaderBuilder {
+        if self.cache.is_none() {
+            self.cache = ProgramCache::from_env();
+        }
+        self
+    }
+
+    pub fn build(&self) -> Result<Shader, ShaderError> {
+        self.validate()?;
+
+        // 1. retrieve the source code from filesystem, resolve #includes and add the defines
+        let mut sources = Vec::with_capacity(self.stages.len());
+        for &(stage, ref stageSource) in &self.stages {
+            let (name, mut source) = match *stageSource {
+                StageSource::File(ref path) => (path.as_str(), preprocessor::preprocess(path)?),
+                StageSource::Code { ref name, ref code } => (name.as_str(), preprocessor::preprocess_code(code, name)?),
+            };
+            preprocessor::inject_defines(&mut source, &self.defines);
+            sources.push((stage, name, source));
+        }
+
         unsafe {
-            // vertex shader
-            let vertex = gl::CreateShader(gl::VERTEX_SHADER);
-            gl::ShaderSource(vertex, 1, &vShaderCode.as_ptr(), ptr::null());
-            gl::CompileShader(vertex);
-            shader.checkCompileErrors(vertex, "VERTEX");
-            // fragment Shader
-            let fragment = gl::CreateShader(gl::FRAGMENT_SHADER);
-            gl::ShaderSource(fragment, 1, &fShaderCode.as_ptr(), ptr::null());
-            gl::CompileShader(fragment);
-            shader.checkCompileErrors(fragment, "FRAGMENT");
-            // geometry shader
-            let geometry = gl::CreateShader(gl::GEOMETRY_SHADER);
-            gl::ShaderSource(geometry, 1, &gShaderCode.as_ptr(), ptr::null());
-            gl::CompileShader(geometry);
-            shader.checkCompileErrors(geometry, "GEOMETRY");
+            // try the binary cache first
+            let cache = match self.cache {
+                Some(ref cache) if ProgramCache::supported() => Some(cache),
+                _ => None,
+            };
+            let cacheKey = cache.map(|cache| {
+                let codes: Vec<(&str, &str)> = sources.iter()
+                    .map(|&(stage, _, ref source)| (stage.name(), source.code.as_str()))
+                    .collect();
+                cache.key(&codes)
+            });
+            if let (Some(cache), Some(key)) = (cache, cacheKey) {
+                if let Some(ID) = cache.load(key) {
+                    return Ok(Shader::fromProgram(ID));
+                }
+            }
+
+            // 2. compile shaders
+            let mut shaders = Vec::with_capacity(sources.len());
+            for &(stage, name, ref source) in &sources {
+                match compileShader(stage, name, source) {
+                    Ok(shader) => shaders.push(shader),
+                    Err(err) => {
+                        for &shader in &shaders {
+                            gl::DeleteShader(shader);
+                        }
+                        return Err(err);
+                    }
+                }
+            }
 
             // shader Program
             let ID = gl::CreateProgram();
-            gl::AttachShader(ID, vertex);
-            gl::AttachShader(ID, fragment);
-            gl::AttachShader(ID, geometry);
+            if cache.is_some() {
+                gl::ProgramParameteri(ID, gl::PROGRAM_BINARY_RETRIEVABLE_HINT, gl::TRUE as GLint);
+            }
+            for &shader in &shaders {
+                gl::AttachShader(ID, shader);
+            }
             gl::LinkProgram(ID);
-            shader.checkCompileErrors(ID, "PROGRAM");
             // delete the shaders as they're linked into our program now and no longer necessary
-            gl::DeleteShader(vertex);
-            gl::DeleteShader(fragment);
-            gl::DeleteShader(geometry);
-            shader.ID = ID;
+            for &shader in &shaders {
+                gl::DeleteShader(shader);
+            }
+
+            let mut success = gl::FALSE as GLint;
+            gl::GetProgramiv(ID, gl::LINK_STATUS, &mut success);
+            if success != gl::TRUE as GLint {
+                // the source string numbers in a link log can't be mapped to files: each stage
+                // numbers its own #includes (see `Source::files`), so the log is kept as it is
+                let log = InfoLog::parse(&programInfoLog(ID), &[]);
+                gl::DeleteProgram(ID);
+                return Err(ShaderError::Link { log });
+            }
+
+            if let (Some(cache), Some(key)) = (cache, cacheKey) {
+                if let Err(err) = cache.store(key, ID) {
+                    println!("WARNING::SHADER: failed to store program binary in {:?}: {}", cache.directory, err);
+                }
+            }
+
+            Ok(Shader::fromProgram(ID))
+        }
+    }
+
+    fn validate(&self) -> Result<(), ShaderError> {
+        let has = |stage| self.stages.iter().any(|&(s, _)| s == stage);
+        let invalid = |message: &str| Err(ShaderError::InvalidStages(message.to_string()));
+
+        if self.stages.is_empty() {
+            return invalid("no stages");
+        }
+        let mut seen = Vec::new();
+        for &(stage, _) in &self.stages {
+            if seen.contains(&stage) {
+                return Err(ShaderError::InvalidStages(format!("more than one {} stage", stage)));
+            }
+            seen.push(stage);
+        }
+        if has(ShaderStage::Compute) && self.stages.len() > 1 {
+            return invalid("a compute shader can't be combined with other stages");
         }
+        if !has(ShaderStage::Compute) && !has(ShaderStage::Vertex) {
+            return invalid("missing vertex stage");
+        }
+        if has(ShaderStage::TessControl) && !has(ShaderStage::TessEvaluation) {
+            return invalid("a tessellation control stage requires a tessellation evaluation stage");
+        }
+        Ok(())
+    }
+}
+
+/// compiles a single stage; the shader object is deleted again if compilation fails.
+unsafe fn compileShader(stage: ShaderStage, path: &str, source: &Source) -> Result<u32, ShaderError> {
+    let code = CString::new(source.code.as_bytes()).map_err(|error| ShaderError::Io {
+        path: path.to_string(),
+        error: io::Error::new(io::ErrorKind::InvalidData, error),
+    })?;
+
+    let shader = gl::CreateShader(stage.glType());
+    gl::ShaderSource(shader, 1, &code.as_ptr(), ptr::null());
+    gl::CompileShader(shader);
+
+    let mut success = gl::FALSE as GLint;
+    gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
+    if success != gl::TRUE as GLint {
+        let files: Vec<&str> = source.files.iter().map(String::as_str).collect();
+        let log = InfoLog::parse(&shaderInfoLog(shader), &files);
+        gl::DeleteShader(shader);
+        return Err(ShaderError::Compile { stage, path: path.to_string(), log });
+    }
+    Ok(shader)
+}
+
+unsafe fn shaderInfoLog(shader: u32) -> String {
+    let mut length = 0;
+    gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut length);
+    let mut infoLog = vec![0u8; length.max(1) as usize];
+    gl::GetShaderInfoLog(shader, length, ptr::null_mut(), infoLog.as_mut_ptr() as *mut GLchar);
+    infoLogToString(infoLog)
+}
+
+unsafe fn programInfoLog(program: u32) -> String {
+    let mut length = 0;
+    gl::GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut length);
+    let mut infoLog = vec![0u8; length.max(1) as usize];
+    gl::GetProgramInfoLog(program, length, ptr::null_mut(), infoLog.as_mut_ptr() as *mut GLchar);
+    infoLogToString(infoLog)
+}
 
-        shader
+fn infoLogToString(mut infoLog: Vec<u8>) -> String {
+    // strip the trailing null character(s)
+    while infoLog.last() == Some(&0) {
+        infoLog.pop();
     }
+    String::from_utf8_lossy(&infoLog).into_owned()
 }
@@ -0,0 +1,71 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// Compile-time variants of one shader: the same sources built with different sets of
+/// preprocessor defines, each compiled the first time it's requested and kept around afterwards.
+///
+///     let mut lighting = ShaderPermutations::new(ShaderBuilder::new()
+///         .vertex_file("src/_5_advanced_lighting/shaders/1.advanced_lighting.vs")
+///         .fragment_file("src/_5_advanced_lighting/shaders/1.advanced_lighting.fs"));
+///     let shader = lighting.get(if blinn { &[("BLINN", "")] } else { &[] })?;
+///     shader.useProgram();
+///
+/// Unlike branching on a uniform, the disabled code paths don't exist in the compiled program.
+
+use std::collections::HashMap;
+use std::collections::hash_map;
+
+use gl;
+
+use preprocessor::Defines;
+use shader::{Shader, ShaderBuilder, ShaderError};
+
+pub struct ShaderPermutations {
+    /// stages, cache and defines shared by all permutations
+    builder: ShaderBuilder,
+    programs: HashMap<Defines, Shader>,
+}
+
+impl ShaderPermutations {
+    pub fn new(builder: ShaderBuilder) -> ShaderPermutations {
+        ShaderPermutations { builder, programs: HashMap::new() }
+    }
+
+    /// The program for the given (name, value) defines, compiled if this set wasn't requested before.
+    /// The order of the defines doesn't matter; a failed build isn't cached, so it's retried next time.
+    pub fn get(&mut self, defines: &[(&str, &str)]) -> Result<&Shader, ShaderError> {
+        let defines: Defines = defines.iter()
+            .map(|&(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        self.get_defines(defines)
+    }
+
+    pub fn get_defines(&mut self, defines: Defines) -> Result<&Shader, ShaderError> {
+        match self.programs.entry(defines) {
+            hash_map::Entry::Occupied(entry) => Ok(entry.into_mut()),
+            hash_map::Entry::Vacant(entry) => {
+                let shader = self.builder.clone().defines(entry.key()).build()?;
+                Ok(entry.insert(shader))
+            }
+        }
+    }
+
+    /// Builds all given permutations up front, e.g. to avoid a hitch the first time one is used.
+    pub fn precompile(&mut self, permutations: &[&[(&str, &str)]]) -> Result<(), ShaderError> {
+        for defines in permutations {
+            self.get(defines)?;
+        }
+        Ok(())
+    }
+
+    /// The already compiled permutations, e.g. to set uniforms that are shared by all of them.
+    pub fn compiled(&self) -> impl Iterator<Item = (&Defines, &Shader)> {
+        self.programs.iter()
+    }
+
+    /// deletes all compiled programs; they are rebuilt on demand
+    pub unsafe fn clear(&mut self) {
+        for (_, shader) in self.programs.drain() {
+            gl::DeleteProgram(shader.ID);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// Hot-reloading on top of `Shader`: edit a shader file while the tutorial is running and see the
+/// result on the next frame. `ReloadableShader` derefs to `Shader`, so in a render loop only the
+/// constructor changes, plus one call per frame:
+///
+///     let mut shader = ReloadableShader::new("...vs", "...fs");
+///     while !window.should_close() {
+///         shader.reload_if_changed();
+///         ...
+///     }
+
+use std::fs;
+use std::ops::{Deref, DerefMut};
+use std::time::{Duration, Instant, SystemTime};
+
+use gl;
+use gl::types::*;
+
+use preprocessor;
+use shader::{Shader, ShaderError, ShaderStage};
+
+/// how often `reload_if_changed` actually looks at the file system
+const CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+pub struct ReloadableShader {
+    shader: Shader,
+    stages: Vec<(ShaderStage, String)>,
+    /// every file the program was built from, with its modification time at the last (re)load
+    watched: Vec<(String, Option<SystemTime>)>,
+    lastCheck: Instant,
+}
+
+impl Deref for ReloadableShader {
+    type Target = Shader;
+
+    fn deref(&self) -> &Shader {
+        &self.shader
+    }
+}
+
+impl DerefMut for ReloadableShader {
+    fn deref_mut(&mut self) -> &mut Shader {
+        &mut self.shader
+    }
+}
+
+impl ReloadableShader {
+    /// Panics if the initial build fails, just like `Shader::new`
+    pub fn new(vertexPath: &str, fragmentPath: &str) -> ReloadableShader {
+        ReloadableShader::from_files(&[
+            (ShaderStage::Vertex, vertexPath),
+            (ShaderStage::Fragment, fragmentPath),
+        ]).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn with_geometry_shader(vertexPath: &str, fragmentPath: &str, geometryPath: &str) -> ReloadableShader {
+        ReloadableShader::from_files(&[
+            (ShaderStage::Vertex, vertexPath),
+            (ShaderStage::Fragment, fragmentPath),
+            (ShaderStage::Geometry, geometryPath),
+        ]).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn from_files(stages: &[(ShaderStage, &str)]) -> Result<ReloadableShader, ShaderError> {
+        let stages: Vec<(ShaderStage, String)> = stages.iter()
+            .map(|&(stage, path)| (stage, path.to_string()))
+            .collect();
+        let watched = watchList(&stages);
+        let shader = build(&stages)?;
+        Ok(ReloadableShader { shader, stages, watched, lastCheck: Instant::now() })
+    }
+
+    /// Call once per frame: recompiles the program if any of its files changed on disk since the
+    /// last (re)load. Returns true if the program was replaced.
+    /// A failed rebuild is printed and the previous program stays active.
+    pub fn reload_if_changed(&mut self) -> bool {
+        if self.lastCheck.elapsed() < CHECK_INTERVAL {
+            return false;
+        }
+        self.lastCheck = Instant::now();
+
+        let changed = self.watched.iter().any(|&(ref path, modified)| modifiedTime(path) != modified);
+        if !changed {
+            return false;
+        }
+        // remember the new times even if the build fails, so a broken file is only reported once
+        self.watched = watchList(&self.stages);
+        match self.reload() {
+            Ok(()) => true,
+            Err(err) => {
+                println!("{}\nkeeping the previous shader program", err);
+                false
+            }
+        }
+    }
+
+    /// Unconditionally rebuilds the program, e.g. when a reload key is pressed.
+    /// Uniform values of the old program are carried over to the new one, since render loops
+    /// usually set things like sampler units only once before the loop.
+    pub fn reload(&mut self) -> Result<(), ShaderError> {
+        let mut shader = build(&self.stages)?;
+        shader.strict = self.shader.strict;
+        unsafe {
+            copyUniforms(&self.shader, &shader);
+            gl::DeleteProgram(self.shader.ID);
+        }
+        self.shader = shader;
+        println!("reloaded shader {}", self.stages.iter().map(|s| s.1.as_str()).collect::<Vec<_>>().join(", "));
+        Ok(())
+    }
+}
+
+fn build(stages: &[(ShaderStage, String)]) -> Result<Shader, ShaderError> {
+    let stages: Vec<(ShaderStage, &str)> = stages.iter().map(|&(stage, ref path)| (stage, path.as_str())).collect();
+    Shader::from_files(&stages)
+}
+
+/// the stage files plus everything they #include
+fn watchList(stages: &[(ShaderStage, String)]) -> Vec<(String, Option<SystemTime>)> {
+    let mut files: Vec<String> = Vec::new();
+    for &(_, ref path) in stages {
+        match preprocessor::preprocess(path) {
+            Ok(source) => files.extend(source.files),
+            Err(_) => files.push(path.clone()),
+        }
+    }
+    files.sort();
+    files.dedup();
+    files.into_iter()
+        .map(|path| { let modified = modifiedTime(&path); (path, modified) })
+        .collect()
+}
+
+fn modifiedTime(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// copies the current values of all active uniforms of `from` to the uniforms with the same name and type in `to`
+unsafe fn copyUniforms(from: &Shader, to: &Shader) {
+    let mut previous = 0;
+    gl::GetIntegerv(gl::CURRENT_PROGRAM, &mut previous);
+    gl::UseProgram(to.ID);
+
+    for (name, source) in &from.uniforms {
+        if let Some(target) = to.uniforms.get(name) {
+            if target.type_ == source.type_ {
+                copyUniform(from.ID, source.location, target.location, source.type_);
+            }
+        }
+    }
+
+    gl::UseProgram(previous as u32);
+}
+
+/// expects `to` to be the current program
+unsafe fn copyUniform(from: u32, source: i32, target: i32, type_: GLenum) {
+    let mut f = [0f32; 16];
+    let mut i = [0i32; 4];
+    match type_ {
+        gl::FLOAT => { gl::GetUniformfv(from, source, f.as_mut_ptr()); gl::Uniform1fv(target, 1, f.as_ptr()) }
+        gl::FLOAT_VEC2 => { gl::GetUniformfv(from, source, f.as_mut_ptr()); gl::Uniform2fv(target, 1, f.as_ptr()) }
+        gl::FLOAT_VEC3 => { gl::GetUniformfv(from, source, f.as_mut_ptr()); gl::Uniform3fv(target, 1, f.as_ptr()) }
+        gl::FLOAT_VEC4 => { gl::GetUniformfv(from, source, f.as_mut_ptr()); gl::Uniform4fv(target, 1, f.as_ptr()) }
+        gl::FLOAT_MAT2 => { gl::GetUniformfv(from, source, f.as_mut_ptr()); gl::UniformMatrix2fv(target, 1, gl::FALSE, f.as_ptr()) }
+        gl::FLOAT_MAT3 => { gl::GetUniformfv(from, source, f.as_mut_ptr()); gl::UniformMatrix3fv(target, 1, gl::FALSE, f.as_ptr()) }
+        gl::FLOAT_MAT4 => { gl::GetUniformfv(from, source, f.as_mut_ptr()); gl::UniformMatrix4fv(target, 1, gl::FALSE, f.as_ptr()) }
+        gl::INT_VEC2 | gl::BOOL_VEC2 => { gl::GetUniformiv(from, source, i.as_mut_ptr()); gl::Uniform2iv(target, 1, i.as_ptr()) }
+        gl::INT_VEC3 | gl::BOOL_VEC3 => { gl::GetUniformiv(from, source, i.as_mut_ptr()); gl::Uniform3iv(target, 1, i.as_ptr()) }
+        gl::INT_VEC4 | gl::BOOL_VEC4 => { gl::GetUniformiv(from, source, i.as_mut_ptr()); gl::Uniform4iv(target, 1, i.as_ptr()) }
+        gl::UNSIGNED_INT => { let mut u = 0; gl::GetUniformuiv(from, source, &mut u); gl::Uniform1ui(target, u) }
+        // int, bool and all the sampler types
+        _ => { gl::GetUniformiv(from, source, i.as_mut_ptr()); gl::Uniform1iv(target, 1, i.as_ptr()) }
+    }
+}
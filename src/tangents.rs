@@ -0,0 +1,79 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// Per-vertex tangent frames for normal mapping, computed from positions, normals and texture
+/// coordinates like `renderQuad` in 5.4 does by hand for its two triangles. `Model` uses this when
+/// it loads a mesh, to fill `Vertex::Tangent` and `Vertex::Bitangent`.
+///
+/// The conventions are MikkTSpace's (what Blender, Substance and most normal map bakers use):
+/// the tangent follows +U, is orthogonal to the vertex normal, and its `w` is the handedness, so the
+/// bitangent is `w * cross(normal, tangent)` and points along +V. The contributions of the triangles
+/// around a vertex are weighted by their angle at the vertex. Unlike the full MikkTSpace algorithm,
+/// vertices aren't split: a vertex shared by triangles with mirrored UVs gets an averaged frame.
+
+use cgmath::{Vector2, Vector3, Vector4};
+use cgmath::prelude::*;
+
+const EPSILON: f32 = 1e-12;
+
+/// One tangent per vertex (xyz, with the handedness +1/-1 in w) for an indexed triangle list.
+/// Vertices without usable texture coordinates (e.g. all triangles around them have degenerate UVs)
+/// get an arbitrary tangent orthogonal to the normal.
+pub fn generate_tangents(positions: &[Vector3<f32>], normals: &[Vector3<f32>], texCoords: &[Vector2<f32>], indices: &[u32]) -> Vec<Vector4<f32>> {
+    assert!(positions.len() == normals.len() && positions.len() == texCoords.len(), "vertex attributes of different lengths");
+    let mut tangents = vec![Vector3::zero(); positions.len()];
+    let mut bitangents = vec![Vector3::zero(); positions.len()];
+
+    for triangle in indices.chunks(3).filter(|triangle| triangle.len() == 3) {
+        let i = [triangle[0] as usize, triangle[1] as usize, triangle[2] as usize];
+        let (p0, p1, p2) = (positions[i[0]], positions[i[1]], positions[i[2]]);
+        let (uv0, uv1, uv2) = (texCoords[i[0]], texCoords[i[1]], texCoords[i[2]]);
+        let (edge1, edge2) = (p1 - p0, p2 - p0);
+        let (deltaUV1, deltaUV2) = (uv1 - uv0, uv2 - uv0);
+
+        let determinant = deltaUV1.x * deltaUV2.y - deltaUV2.x * deltaUV1.y;
+        if determinant.abs() < EPSILON {
+            continue; // the texture is stretched to a line or point on this triangle
+        }
+        let f = 1.0 / determinant;
+        let tangent = (edge1 * deltaUV2.y - edge2 * deltaUV1.y) * f;
+        let bitangent = (edge2 * deltaUV1.x - edge1 * deltaUV2.x) * f;
+
+        let corners = [(i[0], p1 - p0, p2 - p0), (i[1], p2 - p1, p0 - p1), (i[2], p0 - p2, p1 - p2)];
+        for &(vertex, a, b) in &corners {
+            let weight = angle(a, b);
+            tangents[vertex] += tangent * weight;
+            bitangents[vertex] += bitangent * weight;
+        }
+    }
+
+    normals.iter().zip(tangents.iter().zip(&bitangents))
+        .map(|(&normal, (&tangent, &bitangent))| {
+            // Gram-Schmidt: remove the part along the normal
+            let tangent = tangent - normal * normal.dot(tangent);
+            let tangent = if tangent.magnitude2() > EPSILON { tangent.normalize() } else { perpendicular(normal) };
+            let handedness = if normal.cross(tangent).dot(bitangent) < 0.0 { -1.0 } else { 1.0 };
+            tangent.extend(handedness)
+        })
+        .collect()
+}
+
+/// The bitangent of a tangent returned by `generate_tangents`, as a shader would reconstruct it
+pub fn bitangent(normal: Vector3<f32>, tangent: Vector4<f32>) -> Vector3<f32> {
+    normal.cross(tangent.truncate()) * tangent.w
+}
+
+/// angle between two edges of a triangle, 0 for degenerate edges
+fn angle(a: Vector3<f32>, b: Vector3<f32>) -> f32 {
+    let lengths = (a.magnitude2() * b.magnitude2()).sqrt();
+    if lengths < EPSILON {
+        return 0.0;
+    }
+    (a.dot(b) / lengths).max(-1.0).min(1.0).acos()
+}
+
+/// some unit vector orthogonal to `normal`
+fn perpendicular(normal: Vector3<f32>) -> Vector3<f32> {
+    let axis = if normal.x.abs() < 0.9 { Vector3::unit_x() } else { Vector3::unit_y() };
+    let tangent = axis - normal * normal.dot(axis);
+    if tangent.magnitude2() > EPSILON { tangent.normalize() } else { axis }
+}
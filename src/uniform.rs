@@ -0,0 +1,195 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// Uniform reflection: after linking, `Shader` asks GL for all active uniforms once and keeps
+/// their locations, so the setters don't need a `glGetUniformLocation` string lookup every frame.
+/// Also the `IntoUniform` trait behind the generic `Shader::set`.
+
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::slice;
+
+use cgmath::{Matrix2, Matrix3, Matrix4, Point3, Vector2, Vector3, Vector4};
+use gl;
+use gl::types::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UniformInfo {
+    pub location: GLint,
+    /// GL type enum of a single element, e.g. `gl::FLOAT_VEC3`
+    pub type_: GLenum,
+    /// number of array elements from this location on; 1 for non-arrays
+    pub size: GLint,
+}
+
+/// Queries all active uniforms of a linked program.
+/// Arrays are listed under their plain name, `name[0]` and every element `name[i]`;
+/// members of uniform blocks don't have a location and are skipped.
+pub unsafe fn activeUniforms(program: u32) -> HashMap<String, UniformInfo> {
+    let mut uniforms = HashMap::new();
+
+    let (mut count, mut maxLength) = (0, 0);
+    gl::GetProgramiv(program, gl::ACTIVE_UNIFORMS, &mut count);
+    gl::GetProgramiv(program, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut maxLength);
+    let mut nameBuffer = vec![0u8; maxLength.max(1) as usize];
+
+    for index in 0..count as u32 {
+        let (mut length, mut size, mut type_) = (0, 0, 0);
+        gl::GetActiveUniform(program, index, nameBuffer.len() as GLsizei, &mut length, &mut size, &mut type_,
+                             nameBuffer.as_mut_ptr() as *mut GLchar);
+        let name = String::from_utf8_lossy(&nameBuffer[..length as usize]).into_owned();
+        let location = uniformLocation(program, &name);
+        if location == -1 {
+            continue;
+        }
+
+        if name.ends_with("[0]") {
+            let base = &name[..name.len() - 3];
+            uniforms.insert(base.to_string(), UniformInfo { location, type_, size });
+            for element in 0..size {
+                let elementName = format!("{}[{}]", base, element);
+                let location = uniformLocation(program, &elementName);
+                uniforms.insert(elementName, UniformInfo { location, type_, size: size - element });
+            }
+        } else {
+            uniforms.insert(name, UniformInfo { location, type_, size });
+        }
+    }
+
+    uniforms
+}
+
+unsafe fn uniformLocation(program: u32, name: &str) -> GLint {
+    let name = CString::new(name).unwrap();
+    gl::GetUniformLocation(program, name.as_ptr())
+}
+
+pub fn isSampler(type_: GLenum) -> bool {
+    match type_ {
+        gl::SAMPLER_1D | gl::SAMPLER_2D | gl::SAMPLER_3D | gl::SAMPLER_CUBE |
+        gl::SAMPLER_1D_SHADOW | gl::SAMPLER_2D_SHADOW | gl::SAMPLER_CUBE_SHADOW |
+        gl::SAMPLER_1D_ARRAY | gl::SAMPLER_2D_ARRAY | gl::SAMPLER_1D_ARRAY_SHADOW | gl::SAMPLER_2D_ARRAY_SHADOW |
+        gl::SAMPLER_2D_MULTISAMPLE | gl::SAMPLER_2D_MULTISAMPLE_ARRAY | gl::SAMPLER_BUFFER | gl::SAMPLER_2D_RECT |
+        gl::SAMPLER_CUBE_MAP_ARRAY | gl::SAMPLER_CUBE_MAP_ARRAY_SHADOW |
+        gl::INT_SAMPLER_1D | gl::INT_SAMPLER_2D | gl::INT_SAMPLER_3D | gl::INT_SAMPLER_CUBE |
+        gl::INT_SAMPLER_2D_ARRAY | gl::INT_SAMPLER_BUFFER |
+        gl::UNSIGNED_INT_SAMPLER_1D | gl::UNSIGNED_INT_SAMPLER_2D | gl::UNSIGNED_INT_SAMPLER_3D |
+        gl::UNSIGNED_INT_SAMPLER_CUBE | gl::UNSIGNED_INT_SAMPLER_2D_ARRAY | gl::UNSIGNED_INT_SAMPLER_BUFFER => true,
+        _ => false,
+    }
+}
+
+/// GLSL name of a uniform type, for error messages
+pub fn typeName(type_: GLenum) -> String {
+    let name = match type_ {
+        gl::FLOAT => "float",
+        gl::FLOAT_VEC2 => "vec2",
+        gl::FLOAT_VEC3 => "vec3",
+        gl::FLOAT_VEC4 => "vec4",
+        gl::INT => "int",
+        gl::INT_VEC2 => "ivec2",
+        gl::INT_VEC3 => "ivec3",
+        gl::INT_VEC4 => "ivec4",
+        gl::UNSIGNED_INT => "uint",
+        gl::BOOL => "bool",
+        gl::BOOL_VEC2 => "bvec2",
+        gl::BOOL_VEC3 => "bvec3",
+        gl::BOOL_VEC4 => "bvec4",
+        gl::FLOAT_MAT2 => "mat2",
+        gl::FLOAT_MAT3 => "mat3",
+        gl::FLOAT_MAT4 => "mat4",
+        gl::SAMPLER_2D => "sampler2D",
+        gl::SAMPLER_3D => "sampler3D",
+        gl::SAMPLER_CUBE => "samplerCube",
+        gl::SAMPLER_2D_SHADOW => "sampler2DShadow",
+        gl::SAMPLER_2D_MULTISAMPLE => "sampler2DMS",
+        _ if isSampler(type_) => "sampler",
+        _ => return format!("<type 0x{:X}>", type_),
+    };
+    name.to_string()
+}
+
+/// A value that can be passed to `Shader::set`: single values and slices of `UniformElement`s.
+pub trait IntoUniform {
+    /// GLSL type name, for error messages
+    fn glslType(&self) -> &'static str;
+    /// whether a uniform declared with the GL type `type_` can be set from this value
+    fn accepts(&self, type_: GLenum) -> bool;
+    /// number of array elements that are uploaded
+    fn count(&self) -> usize;
+    unsafe fn upload(&self, location: GLint);
+}
+
+/// A single uniform value; every element type can also be uploaded as an array from a slice.
+pub trait UniformElement: Sized {
+    const GLSL_TYPE: &'static str;
+    fn accepts(type_: GLenum) -> bool;
+    unsafe fn uploadArray(location: GLint, values: &[Self]);
+}
+
+impl<T: UniformElement> IntoUniform for T {
+    fn glslType(&self) -> &'static str { T::GLSL_TYPE }
+    fn accepts(&self, type_: GLenum) -> bool { T::accepts(type_) }
+    fn count(&self) -> usize { 1 }
+    unsafe fn upload(&self, location: GLint) {
+        T::uploadArray(location, slice::from_ref(self))
+    }
+}
+
+/// e.g. `shader.set(c_str!("lightPositions"), &lightPositions[..])` for `uniform vec3 lightPositions[4]`
+impl<'a, T: UniformElement> IntoUniform for &'a [T] {
+    fn glslType(&self) -> &'static str { T::GLSL_TYPE }
+    fn accepts(&self, type_: GLenum) -> bool { T::accepts(type_) }
+    fn count(&self) -> usize { self.len() }
+    unsafe fn upload(&self, location: GLint) {
+        T::uploadArray(location, self)
+    }
+}
+
+impl UniformElement for f32 {
+    const GLSL_TYPE: &'static str = "float";
+    fn accepts(type_: GLenum) -> bool { type_ == gl::FLOAT }
+    unsafe fn uploadArray(location: GLint, values: &[f32]) {
+        gl::Uniform1fv(location, values.len() as GLsizei, values.as_ptr());
+    }
+}
+
+/// ints are also used for bools and to assign texture units to samplers
+impl UniformElement for i32 {
+    const GLSL_TYPE: &'static str = "int";
+    fn accepts(type_: GLenum) -> bool { type_ == gl::INT || type_ == gl::BOOL || isSampler(type_) }
+    unsafe fn uploadArray(location: GLint, values: &[i32]) {
+        gl::Uniform1iv(location, values.len() as GLsizei, values.as_ptr());
+    }
+}
+
+impl UniformElement for bool {
+    const GLSL_TYPE: &'static str = "bool";
+    fn accepts(type_: GLenum) -> bool { type_ == gl::BOOL }
+    unsafe fn uploadArray(location: GLint, values: &[bool]) {
+        let values: Vec<i32> = values.iter().map(|&value| value as i32).collect();
+        gl::Uniform1iv(location, values.len() as GLsizei, values.as_ptr());
+    }
+}
+
+// cgmath's vector, point and matrix types are repr(C), so a slice of them is a tightly packed float array.
+macro_rules! impl_uniform_element {
+    ($ty:ty, $glsl:expr, $glType:expr, |$location:ident, $count:ident, $ptr:ident| $upload:expr) => {
+        impl UniformElement for $ty {
+            const GLSL_TYPE: &'static str = $glsl;
+            fn accepts(type_: GLenum) -> bool { type_ == $glType }
+            unsafe fn uploadArray($location: GLint, values: &[$ty]) {
+                let $count = values.len() as GLsizei;
+                let $ptr = values.as_ptr() as *const f32;
+                $upload
+            }
+        }
+    }
+}
+
+impl_uniform_element!(Vector2<f32>, "vec2", gl::FLOAT_VEC2, |l, n, p| gl::Uniform2fv(l, n, p));
+impl_uniform_element!(Vector3<f32>, "vec3", gl::FLOAT_VEC3, |l, n, p| gl::Uniform3fv(l, n, p));
+impl_uniform_element!(Vector4<f32>, "vec4", gl::FLOAT_VEC4, |l, n, p| gl::Uniform4fv(l, n, p));
+impl_uniform_element!(Point3<f32>, "vec3", gl::FLOAT_VEC3, |l, n, p| gl::Uniform3fv(l, n, p));
+impl_uniform_element!(Matrix2<f32>, "mat2", gl::FLOAT_MAT2, |l, n, p| gl::UniformMatrix2fv(l, n, gl::FALSE, p));
+impl_uniform_element!(Matrix3<f32>, "mat3", gl::FLOAT_MAT3, |l, n, p| gl::UniformMatrix3fv(l, n, gl::FALSE, p));
+impl_uniform_element!(Matrix4<f32>, "mat4", gl::FLOAT_MAT4, |l, n, p| gl::UniformMatrix4fv(l, n, gl::FALSE, p));
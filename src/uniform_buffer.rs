@@ -0,0 +1,313 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+/// Uniform buffer objects with a Rust struct as their contents.
+///
+/// The `Std140` trait describes how a type is laid out in a `layout (std140)` uniform block
+/// (alignments and sizes as in section 7.6.2.2 of the GL 4.5 spec). Structs implement it with the
+/// `uniform_block!` macro (see `macros.rs`), which computes the size of the whole block at compile
+/// time and writes every field at its padded offset, so the Rust struct itself needs no manual padding:
+///
+///     uniform_block! {
+///         struct Matrices {
+///             projection: Matrix4<f32>,
+///             view: Matrix4<f32>,
+///         }
+///     }
+///
+///     let matrices = UniformBuffer::<Matrices>::new(0);
+///     matrices.bind_block(&shader, c_str!("Matrices"))?;
+///     matrices.update(&Matrices { projection, view });
+
+use std::error::Error;
+use std::ffi::CStr;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ptr;
+
+use cgmath::{Matrix2, Matrix3, Matrix4, Point3, Vector2, Vector3, Vector4};
+use gl;
+use gl::types::*;
+
+use shader::Shader;
+
+/// A type that can be stored in a std140 uniform block
+pub trait Std140 {
+    /// base alignment in bytes
+    const ALIGN: usize;
+    /// size in bytes, without trailing padding that the next member's alignment adds
+    const SIZE: usize;
+    /// Writes the value to the start of `buffer`, which is at least `SIZE` bytes long.
+    /// Padding bytes are left untouched.
+    fn write_std140(&self, buffer: &mut [u8]);
+    /// (name, offset) of the top-level members; only meaningful for blocks created with `uniform_block!`
+    fn std140_members() -> Vec<(&'static str, usize)> {
+        Vec::new()
+    }
+}
+
+/// rounds `offset` up to the next multiple of `align`
+pub const fn std140_align(offset: usize, align: usize) -> usize {
+    (offset + align - 1) / align * align
+}
+
+fn writeFloats(buffer: &mut [u8], values: &[f32]) {
+    for (i, value) in values.iter().enumerate() {
+        buffer[i * 4..i * 4 + 4].copy_from_slice(&value.to_bits().to_ne_bytes());
+    }
+}
+
+impl Std140 for f32 {
+    const ALIGN: usize = 4;
+    const SIZE: usize = 4;
+    fn write_std140(&self, buffer: &mut [u8]) {
+        writeFloats(buffer, &[*self]);
+    }
+}
+
+impl Std140 for i32 {
+    const ALIGN: usize = 4;
+    const SIZE: usize = 4;
+    fn write_std140(&self, buffer: &mut [u8]) {
+        buffer[..4].copy_from_slice(&self.to_ne_bytes());
+    }
+}
+
+impl Std140 for u32 {
+    const ALIGN: usize = 4;
+    const SIZE: usize = 4;
+    fn write_std140(&self, buffer: &mut [u8]) {
+        buffer[..4].copy_from_slice(&self.to_ne_bytes());
+    }
+}
+
+/// GLSL bools in blocks are 4 bytes, 0 or 1
+impl Std140 for bool {
+    const ALIGN: usize = 4;
+    const SIZE: usize = 4;
+    fn write_std140(&self, buffer: &mut [u8]) {
+        (*self as u32).write_std140(buffer);
+    }
+}
+
+impl Std140 for Vector2<f32> {
+    const ALIGN: usize = 8;
+    const SIZE: usize = 8;
+    fn write_std140(&self, buffer: &mut [u8]) {
+        writeFloats(buffer, &[self.x, self.y]);
+    }
+}
+
+/// vec3 is aligned like a vec4, but a following scalar can use its 4th component
+impl Std140 for Vector3<f32> {
+    const ALIGN: usize = 16;
+    const SIZE: usize = 12;
+    fn write_std140(&self, buffer: &mut [u8]) {
+        writeFloats(buffer, &[self.x, self.y, self.z]);
+    }
+}
+
+impl Std140 for Point3<f32> {
+    const ALIGN: usize = 16;
+    const SIZE: usize = 12;
+    fn write_std140(&self, buffer: &mut [u8]) {
+        writeFloats(buffer, &[self.x, self.y, self.z]);
+    }
+}
+
+impl Std140 for Vector4<f32> {
+    const ALIGN: usize = 16;
+    const SIZE: usize = 16;
+    fn write_std140(&self, buffer: &mut [u8]) {
+        writeFloats(buffer, &[self.x, self.y, self.z, self.w]);
+    }
+}
+
+// matrices are stored like an array of their column vectors, so every column takes 16 bytes
+impl Std140 for Matrix2<f32> {
+    const ALIGN: usize = 16;
+    const SIZE: usize = 2 * 16;
+    fn write_std140(&self, buffer: &mut [u8]) {
+        self.x.write_std140(&mut buffer[0..]);
+        self.y.write_std140(&mut buffer[16..]);
+    }
+}
+
+impl Std140 for Matrix3<f32> {
+    const ALIGN: usize = 16;
+    const SIZE: usize = 3 * 16;
+    fn write_std140(&self, buffer: &mut [u8]) {
+        self.x.write_std140(&mut buffer[0..]);
+        self.y.write_std140(&mut buffer[16..]);
+        self.z.write_std140(&mut buffer[32..]);
+    }
+}
+
+impl Std140 for Matrix4<f32> {
+    const ALIGN: usize = 16;
+    const SIZE: usize = 4 * 16;
+    fn write_std140(&self, buffer: &mut [u8]) {
+        self.x.write_std140(&mut buffer[0..]);
+        self.y.write_std140(&mut buffer[16..]);
+        self.z.write_std140(&mut buffer[32..]);
+        self.w.write_std140(&mut buffer[48..]);
+    }
+}
+
+// array elements are aligned to 16 bytes, even for scalars (e.g. a `float[4]` takes 64 bytes)
+macro_rules! impl_std140_array {
+    ($($n:expr),*) => {
+        $(
+            impl<T: Std140> Std140 for [T; $n] {
+                const ALIGN: usize = std140_align(T::ALIGN, 16);
+                const SIZE: usize = $n * std140_align(T::SIZE, 16);
+                fn write_std140(&self, buffer: &mut [u8]) {
+                    let stride = std140_align(T::SIZE, 16);
+                    for (i, element) in self.iter().enumerate() {
+                        element.write_std140(&mut buffer[i * stride..]);
+                    }
+                }
+            }
+        )*
+    }
+}
+
+impl_std140_array!(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 32, 64, 128);
+
+#[derive(Debug)]
+pub enum UniformBlockError {
+    /// the program has no active uniform block with this name
+    NotFound { block: String },
+    /// the block in the shader is larger than the Rust type
+    SizeMismatch { block: String, expected: usize, actual: usize },
+    /// a member is at a different offset in the shader, e.g. because the block isn't `std140`
+    /// or the members are declared in a different order
+    OffsetMismatch { block: String, member: String, expected: usize, actual: usize },
+}
+
+impl fmt::Display for UniformBlockError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            UniformBlockError::NotFound { ref block } =>
+                write!(f, "no active uniform block '{}'", block),
+            UniformBlockError::SizeMismatch { ref block, expected, actual } =>
+                write!(f, "uniform block '{}' needs {} bytes, but the Rust type only has {}", block, actual, expected),
+            UniformBlockError::OffsetMismatch { ref block, ref member, expected, actual } =>
+                write!(f, "member '{}' of uniform block '{}' is at offset {} in the shader but at {} in the Rust type",
+                       member, block, actual, expected),
+        }
+    }
+}
+
+impl Error for UniformBlockError {}
+
+/// A uniform buffer holding one `T`, permanently bound to a uniform binding point
+pub struct UniformBuffer<T: Std140> {
+    pub ID: u32,
+    pub binding: u32,
+    marker: PhantomData<T>,
+}
+
+impl<T: Std140> UniformBuffer<T> {
+    /// Creates the buffer (with undefined contents until the first `update`) and binds it to `binding`.
+    pub unsafe fn new(binding: u32) -> UniformBuffer<T> {
+        let mut ID = 0;
+        gl::GenBuffers(1, &mut ID);
+        gl::BindBuffer(gl::UNIFORM_BUFFER, ID);
+        gl::BufferData(gl::UNIFORM_BUFFER, Self::size() as GLsizeiptr, ptr::null(), gl::DYNAMIC_DRAW);
+        gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+        gl::BindBufferBase(gl::UNIFORM_BUFFER, binding, ID);
+        UniformBuffer { ID, binding, marker: PhantomData }
+    }
+
+    /// the buffer size; blocks are padded to a multiple of 16 bytes
+    fn size() -> usize {
+        std140_align(T::SIZE, 16)
+    }
+
+    /// Connects the uniform block `name` of `shader` to this buffer's binding point, after checking
+    /// that the block's size and member offsets match the std140 layout of `T`.
+    pub unsafe fn bind_block(&self, shader: &Shader, name: &CStr) -> Result<(), UniformBlockError> {
+        let block = name.to_string_lossy().into_owned();
+        let index = gl::GetUniformBlockIndex(shader.ID, name.as_ptr());
+        if index == gl::INVALID_INDEX {
+            return Err(UniformBlockError::NotFound { block });
+        }
+
+        let mut dataSize = 0;
+        gl::GetActiveUniformBlockiv(shader.ID, index, gl::UNIFORM_BLOCK_DATA_SIZE, &mut dataSize);
+        if dataSize as usize > Self::size() {
+            return Err(UniformBlockError::SizeMismatch { block, expected: Self::size(), actual: dataSize as usize });
+        }
+
+        let offsets = blockMemberOffsets(shader.ID, index, &block);
+        for (member, expected) in T::std140_members() {
+            let actual = offsets.iter().find(|&&(ref name, _)| name == member).map(|&(_, offset)| offset);
+            match actual {
+                Some(actual) if actual != expected =>
+                    return Err(UniformBlockError::OffsetMismatch { block, member: member.to_string(), expected, actual }),
+                // members that aren't active in this program are fine
+                _ => {}
+            }
+        }
+
+        gl::UniformBlockBinding(shader.ID, index, self.binding);
+        Ok(())
+    }
+
+    /// uploads the whole struct, including padding
+    pub unsafe fn update(&self, value: &T) {
+        let mut data = vec![0u8; Self::size()];
+        value.write_std140(&mut data);
+        gl::BindBuffer(gl::UNIFORM_BUFFER, self.ID);
+        gl::BufferSubData(gl::UNIFORM_BUFFER, 0, data.len() as GLsizeiptr, data.as_ptr() as *const _);
+        gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+    }
+
+    pub unsafe fn delete(self) {
+        gl::DeleteBuffers(1, &self.ID);
+    }
+}
+
+/// (name, offset) of every active top-level member of a uniform block
+unsafe fn blockMemberOffsets(program: u32, block: u32, blockName: &str) -> Vec<(String, usize)> {
+    let mut count = 0;
+    gl::GetActiveUniformBlockiv(program, block, gl::UNIFORM_BLOCK_ACTIVE_UNIFORMS, &mut count);
+    if count <= 0 {
+        return Vec::new();
+    }
+    let mut indices = vec![0i32; count as usize];
+    gl::GetActiveUniformBlockiv(program, block, gl::UNIFORM_BLOCK_ACTIVE_UNIFORM_INDICES, indices.as_mut_ptr());
+    let indices: Vec<u32> = indices.into_iter().map(|index| index as u32).collect();
+    let mut offsets = vec![0i32; indices.len()];
+    gl::GetActiveUniformsiv(program, count, indices.as_ptr(), gl::UNIFORM_OFFSET, offsets.as_mut_ptr());
+
+    let mut nameLength = 0;
+    gl::GetProgramiv(program, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut nameLength);
+    let mut nameBuffer = vec![0u8; nameLength.max(1) as usize];
+
+    let mut members: Vec<(String, usize)> = Vec::new();
+    for (&index, &offset) in indices.iter().zip(&offsets) {
+        let mut length = 0;
+        gl::GetActiveUniformName(program, index, nameBuffer.len() as GLsizei, &mut length,
+                                 nameBuffer.as_mut_ptr() as *mut GLchar);
+        let name = String::from_utf8_lossy(&nameBuffer[..length as usize]).into_owned();
+        let member = topLevelMember(&name, blockName);
+        // nested struct members and later array elements come after the member's own offset
+        match members.iter_mut().find(|m| m.0 == member) {
+            Some(m) => m.1 = m.1.min(offset as usize),
+            None => members.push((member.to_string(), offset as usize)),
+        }
+    }
+    members
+}
+
+/// `view` -> `view`, `lights[0].color` -> `lights`. Members of blocks with an instance name are
+/// reported with the block name as prefix: `Matrices.view` -> `view`.
+fn topLevelMember<'a>(name: &'a str, blockName: &str) -> &'a str {
+    let name = if name.starts_with(blockName) && name[blockName.len()..].starts_with('.') {
+        &name[blockName.len() + 1..]
+    } else {
+        name
+    };
+    name.split(|c| c == '.' || c == '[').next().unwrap_or(name)
+}
@@ -0,0 +1,42 @@
+#![allow(non_snake_case)]
+//! Reading and writing the camera bookmark file
+
+extern crate cgmath;
+
+mod common;
+
+#[path = "../src/bookmarks.rs"]
+mod bookmarks;
+
+use std::fs;
+
+use cgmath::Point3;
+
+use bookmarks::{Bookmark, Bookmarks};
+
+use common::tempPath;
+
+#[test]
+fn save_and_load_round_trip() {
+    let mut saved = Bookmarks::default();
+    saved.Set(3, Bookmark { position: Point3::new(1.5, -2.0, 30.25), yaw: -90.0, pitch: 12.5, zoom: 30.0, speed: 2.5 });
+    saved.Set(1, Bookmark { position: Point3::new(0.0, 0.0, 3.0), yaw: 45.0, pitch: -89.0, zoom: 45.0, speed: 10.0 });
+
+    let path = tempPath("bookmarks.txt");
+    saved.Save(&path).unwrap();
+    let loaded = Bookmarks::Load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.slots, saved.slots);
+    assert_eq!(loaded.Get(2), None);
+}
+
+#[test]
+fn invalid_lines_report_line_number() {
+    let path = tempPath("bookmarks-invalid.txt");
+    fs::write(&path, "# comment\n\n1 0 0 3 -90 0 45\n").unwrap();
+    let err = Bookmarks::Load(&path).unwrap_err();
+    fs::remove_file(&path).unwrap();
+
+    assert!(err.to_string().starts_with("line 3:"), "{}", err);
+}
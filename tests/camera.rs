@@ -0,0 +1,75 @@
+#![allow(non_snake_case)]
+//! Camera movement without a window
+
+extern crate cgmath;
+
+mod common;
+
+#[path = "../src/bookmarks.rs"]
+mod bookmarks;
+#[path = "../src/camera.rs"]
+mod camera;
+#[path = "../src/orbit_camera.rs"]
+mod orbit_camera;
+
+use cgmath::{Point3, Vector3};
+use cgmath::prelude::*;
+
+use camera::{Camera, CameraController};
+use camera::Camera_Movement::*;
+use orbit_camera::OrbitCamera;
+
+use common::assertClose;
+
+/// moves forward (-Z) for one second in 100 frames at `amount` of the full speed
+fn moveForward<C: CameraController>(camera: &mut C, amount: f32) {
+    for _ in 0..100 {
+        camera.ProcessMovement(FORWARD, amount, 0.01);
+        camera.Update(0.01);
+    }
+}
+
+#[test]
+fn analog_amount_scales_speed() {
+    let mut camera = Camera { MovementSpeed: 2.0, ..Camera::default() };
+    moveForward(&mut camera, 0.5);
+    assertClose(camera.Position.z, -1.0);
+}
+
+#[test]
+fn analog_amount_scales_smooth_movement() {
+    let mut camera = Camera { MovementSpeed: 2.0, Acceleration: 1000.0, ..Camera::default() };
+    moveForward(&mut camera, 0.5);
+    // the velocity is reached almost immediately
+    assertClose(camera.Velocity.magnitude(), 1.0);
+    assert!(camera.Position.z < -0.95 && camera.Position.z > -1.0, "{:?}", camera.Position);
+}
+
+#[test]
+fn zero_damping_stops_at_once() {
+    let mut camera = Camera { MovementSpeed: 2.0, Acceleration: 10.0, ..Camera::default() };
+    moveForward(&mut camera, 1.0);
+    camera.Update(0.01);
+    assert_eq!(camera.Velocity, Vector3::zero());
+    let position = camera.Position;
+    camera.Update(1.0);
+    assert_eq!(camera.Position, position);
+    assert!(camera.Position != Point3::new(0.0, 0.0, 0.0));
+}
+
+/// `OrbitCamera::Frame` moves back until the bounding sphere of a 2x2x2 box fits the narrower field of view
+#[test]
+fn orbit_frame_fits_both_fields_of_view() {
+    let radius = 3f32.sqrt();
+    let halfFovy = 45f32.to_radians() / 2.0;
+    for &aspect in &[2.0, 0.5] {
+        let mut camera = OrbitCamera::default();
+        camera.Aspect = aspect;
+        camera.Frame(Point3::new(1.0, 1.0, 1.0), Point3::new(3.0, 3.0, 3.0));
+        assert_eq!(camera.Target, Point3::new(2.0, 2.0, 2.0));
+        let halfFovx = (halfFovy.tan() * aspect).atan();
+        // the sphere touches the edges of the narrower one and is inside the other
+        assertClose(camera.Distance * halfFovy.min(halfFovx).sin(), radius);
+        assert!(camera.Distance * halfFovy.max(halfFovx).sin() > radius);
+    }
+}
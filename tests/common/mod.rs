@@ -0,0 +1,50 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+//! Helpers shared by the integration tests; each test file includes them with `mod common;` and
+//! uses the ones it needs
+
+use std::env;
+use std::fmt::Debug;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use cgmath::Vector3;
+use cgmath::prelude::*;
+
+/// values `assertClose` can compare
+pub trait Distance: Debug + Copy {
+    fn distance(self, other: Self) -> f32;
+}
+
+impl Distance for f32 {
+    fn distance(self, other: f32) -> f32 {
+        (self - other).abs()
+    }
+}
+
+impl Distance for Vector3<f32> {
+    fn distance(self, other: Vector3<f32>) -> f32 {
+        (self - other).magnitude()
+    }
+}
+
+pub fn assertClose<T: Distance>(a: T, b: T) {
+    assert!(a.distance(b) < 1e-5, "{:?} != {:?}", a, b);
+}
+
+/// `name` in the temp directory, with the process id so concurrent test runs don't share it
+pub fn tempPath(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("learn-opengl-rs-{}-{}", process::id(), name))
+}
+
+/// writes `files` (name, content) to a fresh temp directory for the test `name`
+pub fn writeFiles(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let directory = tempPath(name);
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    for &(file, content) in files {
+        fs::write(directory.join(file), content).unwrap();
+    }
+    directory
+}
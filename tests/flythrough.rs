@@ -0,0 +1,105 @@
+#![allow(non_snake_case)]
+//! Flythrough interpolation and file format
+
+extern crate cgmath;
+
+mod common;
+
+#[path = "../src/bookmarks.rs"]
+mod bookmarks;
+#[path = "../src/camera.rs"]
+mod camera;
+#[path = "../src/flythrough.rs"]
+mod flythrough;
+
+use std::fs;
+
+use cgmath::{Deg, Point3, Quaternion, Vector3};
+use cgmath::prelude::*;
+
+use flythrough::{Flythrough, Keyframe};
+
+use common::tempPath;
+
+fn keyframe(x: f32, y: f32, z: f32, yaw: f32) -> Keyframe {
+    Keyframe { position: Point3::new(x, y, z), orientation: Quaternion::from_angle_y(Deg(yaw)) }
+}
+
+fn assertClose(a: &Keyframe, b: &Keyframe) {
+    assert!((a.position - b.position).magnitude() < 1e-5, "{:?} != {:?}", a, b);
+    // q and -q are the same rotation
+    assert!(a.orientation.dot(b.orientation).abs() > 1.0 - 1e-6, "{:?} != {:?}", a, b);
+}
+
+fn path() -> Flythrough {
+    let mut flythrough = Flythrough::new(10.0);
+    flythrough.keyframes = vec![
+        keyframe(0.0, 0.0, 0.0, 0.0),
+        keyframe(10.0, 0.0, 0.0, 90.0),
+        keyframe(10.0, 2.0, -10.0, 180.0),
+    ];
+    flythrough
+}
+
+#[test]
+fn endpoints_are_exact() {
+    let flythrough = path();
+    assert_eq!(flythrough.Sample(0.0), Some(flythrough.keyframes[0]));
+    assert_eq!(flythrough.Sample(1.0), Some(flythrough.keyframes[2]));
+    // clamped outside of [0, 1]
+    assert_eq!(flythrough.Sample(-0.5), Some(flythrough.keyframes[0]));
+    assert_eq!(flythrough.Sample(2.0), Some(flythrough.keyframes[2]));
+    // every segment takes the same time
+    assertClose(&flythrough.Sample(0.5).unwrap(), &flythrough.keyframes[1]);
+}
+
+#[test]
+fn orientation_is_slerped() {
+    let mut flythrough = path();
+    flythrough.keyframes.truncate(2);
+    let middle = flythrough.Sample(0.5).unwrap();
+    assertClose(&middle, &keyframe(5.0, 0.0, 0.0, 45.0));
+    // the spline eases in and out of the end points, the rotation has a constant speed
+    let quarter = flythrough.Sample(0.25).unwrap();
+    assert!(quarter.position.x < 2.5);
+    assertClose(&quarter, &keyframe(quarter.position.x, 0.0, 0.0, 22.5));
+
+    // the same rotation with the opposite sign still takes the short way round
+    flythrough.keyframes[1].orientation = -flythrough.keyframes[1].orientation;
+    assertClose(&flythrough.Sample(0.5).unwrap(), &middle);
+}
+
+#[test]
+fn no_keyframes() {
+    let mut flythrough = Flythrough::default();
+    assert_eq!(flythrough.Sample(0.5), None);
+    flythrough.Play();
+    assert!(!flythrough.IsPlaying());
+}
+
+#[test]
+fn save_and_load_round_trip() {
+    let mut saved = path();
+    saved.keyframes[1].orientation = Quaternion::from_axis_angle(Vector3::new(1.0, 2.0, 3.0).normalize(), Deg(33.3));
+
+    let path = tempPath("flythrough.txt");
+    saved.Save(&path).unwrap();
+    let loaded = Flythrough::Load(&path, 5.0).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.duration, 5.0);
+    assert_eq!(loaded.keyframes.len(), saved.keyframes.len());
+    for (loaded, saved) in loaded.keyframes.iter().zip(&saved.keyframes) {
+        assertClose(loaded, saved);
+    }
+}
+
+#[test]
+fn invalid_lines_report_line_number() {
+    let path = tempPath("flythrough-invalid.txt");
+    fs::write(&path, "# comment\n0 0 0 1 0 0 0\n1 2 3\n").unwrap();
+    let err = Flythrough::Load(&path, 10.0).err().expect("invalid file was loaded");
+    fs::remove_file(&path).unwrap();
+
+    assert!(err.to_string().starts_with("line 3:"), "{}", err);
+}
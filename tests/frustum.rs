@@ -0,0 +1,156 @@
+#![allow(non_snake_case)]
+//! Frustum plane extraction and culling tests with known projections, without a GL context.
+//! The camera is at the origin looking down -Z, so the projection matrix alone is `projection * view`.
+
+extern crate cgmath;
+
+mod common;
+
+#[path = "../src/bookmarks.rs"]
+mod bookmarks;
+#[path = "../src/bounds.rs"]
+mod bounds;
+#[path = "../src/camera.rs"]
+mod camera;
+#[path = "../src/frustum.rs"]
+mod frustum;
+
+use cgmath::{vec3, Point3, Vector3};
+
+use bounds::{Aabb, BoundingSphere};
+use camera::Projection;
+use frustum::Frustum;
+
+use common::assertClose;
+
+const LEFT: usize = 0;
+const RIGHT: usize = 1;
+const BOTTOM: usize = 2;
+const TOP: usize = 3;
+const NEAR: usize = 4;
+const FAR: usize = 5;
+
+fn sphere(x: f32, y: f32, z: f32, radius: f32) -> BoundingSphere {
+    BoundingSphere { center: Point3::new(x, y, z), radius }
+}
+
+fn cube(x: f32, y: f32, z: f32, halfSize: f32) -> Aabb {
+    let (center, e) = (Point3::new(x, y, z), vec3(halfSize, halfSize, halfSize));
+    Aabb { min: center - e, max: center + e }
+}
+
+/// `sphere` is culled, and the plane `plane` alone is enough to cull it
+fn assertOutsideSphere(frustum: &Frustum, sphere: &BoundingSphere, plane: usize) {
+    assert!(!frustum.intersects_sphere(sphere), "{:?} isn't culled", sphere);
+    assert!(frustum.planes[plane].distance(sphere.center) < -sphere.radius, "{:?} isn't outside plane {}", sphere, plane);
+}
+
+fn assertOutsideAabb(frustum: &Frustum, aabb: &Aabb, plane: usize) {
+    assert!(!frustum.intersects_aabb(aabb), "{:?} isn't culled", aabb);
+    let normal = frustum.planes[plane].normal;
+    // the corner furthest towards the inside is still outside
+    let corner = Point3::new(if normal.x > 0.0 { aabb.max.x } else { aabb.min.x },
+                             if normal.y > 0.0 { aabb.max.y } else { aabb.min.y },
+                             if normal.z > 0.0 { aabb.max.z } else { aabb.min.z });
+    assert!(frustum.planes[plane].distance(corner) < 0.0, "{:?} isn't outside plane {}", aabb, plane);
+}
+
+/// 90° field of view and a square viewport: the side planes are at 45°, `|x| <= distance`
+fn perspectiveFrustum() -> Frustum {
+    Frustum::from_matrix(&Projection::Perspective { near: 0.1, far: 100.0 }.matrix(90.0, 1.0))
+}
+
+#[test]
+fn perspective_spheres() {
+    let frustum = perspectiveFrustum();
+    assert!(frustum.intersects_sphere(&sphere(0.0, 0.0, -10.0, 1.0)));
+    assert!(frustum.contains_point(Point3::new(9.0, -9.0, -10.0)));
+
+    assertOutsideSphere(&frustum, &sphere(-20.0, 0.0, -10.0, 1.0), LEFT);
+    assertOutsideSphere(&frustum, &sphere(20.0, 0.0, -10.0, 1.0), RIGHT);
+    assertOutsideSphere(&frustum, &sphere(0.0, -20.0, -10.0, 1.0), BOTTOM);
+    assertOutsideSphere(&frustum, &sphere(0.0, 20.0, -10.0, 1.0), TOP);
+    assertOutsideSphere(&frustum, &sphere(0.0, 0.0, 1.0, 0.5), NEAR);
+    assertOutsideSphere(&frustum, &sphere(0.0, 0.0, -200.0, 1.0), FAR);
+
+    // the centers are outside, but the spheres reach into the frustum
+    assert!(frustum.intersects_sphere(&sphere(-10.5, 0.0, -10.0, 1.0)));
+    assert!(frustum.intersects_sphere(&sphere(0.0, 10.5, -10.0, 1.0)));
+    assert!(frustum.intersects_sphere(&sphere(0.0, 0.0, 0.5, 1.0)));
+    assert!(frustum.intersects_sphere(&sphere(0.0, 0.0, -100.5, 1.0)));
+}
+
+#[test]
+fn perspective_boxes() {
+    let frustum = perspectiveFrustum();
+    assert!(frustum.intersects_aabb(&cube(0.0, 0.0, -10.0, 1.0)));
+
+    assertOutsideAabb(&frustum, &cube(-20.0, 0.0, -10.0, 1.0), LEFT);
+    assertOutsideAabb(&frustum, &cube(20.0, 0.0, -10.0, 1.0), RIGHT);
+    assertOutsideAabb(&frustum, &cube(0.0, -20.0, -10.0, 1.0), BOTTOM);
+    assertOutsideAabb(&frustum, &cube(0.0, 20.0, -10.0, 1.0), TOP);
+    assertOutsideAabb(&frustum, &cube(0.0, 0.0, 1.0, 0.5), NEAR);
+    assertOutsideAabb(&frustum, &cube(0.0, 0.0, -200.0, 1.0), FAR);
+
+    assert!(frustum.intersects_aabb(&cube(-10.5, 0.0, -10.0, 1.0)));
+    assert!(frustum.intersects_aabb(&cube(0.0, -10.5, -10.0, 1.0)));
+    assert!(frustum.intersects_aabb(&cube(0.0, 0.0, 0.5, 1.0)));
+    assert!(frustum.intersects_aabb(&cube(0.0, 0.0, -100.5, 1.0)));
+    // larger than the whole visible volume
+    assert!(frustum.intersects_aabb(&cube(0.0, 0.0, 0.0, 1000.0)));
+}
+
+#[test]
+fn orthographic() {
+    // x in [-4, 4], y in [-2, 2], distance in [0.1, 10]
+    let frustum = Frustum::from_matrix(&Projection::Orthographic { height: 4.0, near: 0.1, far: 10.0 }.matrix(45.0, 2.0));
+    // the planes are normalized: distances are in world units
+    let center = Point3::new(0.0, 0.0, -5.0);
+    let distances: Vec<f32> = frustum.planes.iter().map(|plane| plane.distance(center)).collect();
+    for (&distance, &expected) in distances.iter().zip(&[4.0, 4.0, 2.0, 2.0, 4.9, 5.0]) {
+        assertClose(distance, expected);
+    }
+    assert_eq!(frustum.planes[LEFT].normal, Vector3::unit_x());
+
+    assert!(frustum.intersects_sphere(&sphere(0.0, 0.0, -5.0, 0.5)));
+    assertOutsideSphere(&frustum, &sphere(-5.0, 0.0, -5.0, 0.5), LEFT);
+    assertOutsideSphere(&frustum, &sphere(5.0, 0.0, -5.0, 0.5), RIGHT);
+    assertOutsideSphere(&frustum, &sphere(0.0, -3.0, -5.0, 0.5), BOTTOM);
+    assertOutsideSphere(&frustum, &sphere(0.0, 3.0, -5.0, 0.5), TOP);
+    assertOutsideSphere(&frustum, &sphere(0.0, 0.0, 0.5, 0.2), NEAR);
+    assertOutsideSphere(&frustum, &sphere(0.0, 0.0, -11.0, 0.5), FAR);
+    assert!(frustum.intersects_sphere(&sphere(4.2, 0.0, -5.0, 0.5)));
+
+    assert!(frustum.intersects_aabb(&cube(0.0, 0.0, -5.0, 0.5)));
+    assertOutsideAabb(&frustum, &cube(-5.0, 0.0, -5.0, 0.5), LEFT);
+    assertOutsideAabb(&frustum, &cube(5.0, 0.0, -5.0, 0.5), RIGHT);
+    assertOutsideAabb(&frustum, &cube(0.0, -3.0, -5.0, 0.5), BOTTOM);
+    assertOutsideAabb(&frustum, &cube(0.0, 3.0, -5.0, 0.5), TOP);
+    assertOutsideAabb(&frustum, &cube(0.0, 0.0, 0.5, 0.2), NEAR);
+    assertOutsideAabb(&frustum, &cube(0.0, 0.0, -11.0, 0.5), FAR);
+    assert!(frustum.intersects_aabb(&cube(0.0, 2.2, -5.0, 0.5)));
+}
+
+#[test]
+fn infinite_reverse_z() {
+    let frustum = Frustum::from_matrix(&Projection::InfiniteReverseZ { near: 0.1 }.matrix(90.0, 1.0));
+    assert!(frustum.intersects_sphere(&sphere(0.0, 0.0, -10.0, 1.0)));
+    // there is no far plane
+    assert!(frustum.intersects_sphere(&sphere(0.0, 0.0, -1.0e6, 1.0)));
+    assert!(frustum.intersects_aabb(&cube(0.0, 0.0, -1.0e6, 1.0)));
+
+    assert!(!frustum.intersects_sphere(&sphere(-20.0, 0.0, -10.0, 1.0)));
+    assert!(!frustum.intersects_sphere(&sphere(0.0, 20.0, -10.0, 1.0)));
+    assert!(!frustum.intersects_aabb(&cube(20.0, 0.0, -10.0, 1.0)));
+    assert!(!frustum.intersects_aabb(&cube(0.0, -20.0, -10.0, 1.0)));
+    // behind the camera and in front of the near plane
+    assert!(!frustum.intersects_sphere(&sphere(0.0, 0.0, 5.0, 1.0)));
+    assert!(!frustum.intersects_sphere(&sphere(0.0, 0.0, -0.05, 0.01)));
+    assert!(!frustum.intersects_aabb(&cube(0.0, 0.0, 5.0, 1.0)));
+
+    // straddling the near plane and a side plane
+    assert!(frustum.intersects_sphere(&sphere(0.0, 0.0, 0.5, 1.0)));
+    assert!(frustum.intersects_aabb(&cube(0.0, 0.0, 0.5, 1.0)));
+    assert!(frustum.intersects_sphere(&sphere(-10.5, 0.0, -10.0, 1.0)));
+    assert!(frustum.intersects_aabb(&cube(-10.5, 0.0, -10.0, 1.0)));
+}
@@ -0,0 +1,61 @@
+#![allow(non_snake_case)]
+//! Gamepad axis mapping with synthetic axis values, without a window or device
+
+extern crate cgmath;
+
+mod common;
+
+#[path = "../src/gamepad.rs"]
+mod gamepad;
+
+use gamepad::{AxisCurve, GamepadConfig};
+
+use common::assertClose;
+
+#[test]
+fn dead_zone_and_curve() {
+    let curve = AxisCurve { dead_zone: 0.2, exponent: 2.0, sensitivity: 1.0 };
+    assert_eq!(curve.apply(0.1), 0.0);
+    assert_eq!(curve.apply(-0.2), 0.0);
+    assertClose(curve.apply(0.6), 0.25);
+    assertClose(curve.apply(-0.6), -0.25);
+    assertClose(curve.apply(1.0), 1.0);
+    // some controllers report slightly more than 1
+    assertClose(curve.apply(1.05), 1.0);
+
+    let linear = AxisCurve { dead_zone: 0.0, exponent: 1.0, sensitivity: 2.0 };
+    assertClose(linear.apply(0.5), 1.0);
+}
+
+#[test]
+fn radial_dead_zone_keeps_direction() {
+    let curve = AxisCurve { dead_zone: 0.2, exponent: 1.0, sensitivity: 1.0 };
+    assert_eq!(curve.apply_stick(0.1, 0.1), (0.0, 0.0));
+    // a diagonal that is inside the per-axis dead zone on neither axis stays diagonal
+    let (x, y) = curve.apply_stick(0.5, 0.5);
+    assertClose(x, y);
+    assertClose((x * x + y * y).sqrt(), (0.5f32.hypot(0.5) - 0.2) / 0.8);
+}
+
+#[test]
+fn default_layout() {
+    let config = GamepadConfig { move_curve: AxisCurve { dead_zone: 0.1, exponent: 1.0, sensitivity: 1.0 }, ..GamepadConfig::default() };
+    // left stick pushed forward (negative y with GLFW), right trigger fully pressed, left trigger at rest
+    let input = config.map(&[0.0, -1.0, 0.0, 0.0, -1.0, 1.0], 0.5);
+    assertClose(input.forward, 1.0);
+    assertClose(input.right, 0.0);
+    assertClose(input.up, 1.0);
+    assert_eq!((input.look_x, input.look_y), (0.0, 0.0));
+
+    // right stick fully right for half a second
+    let input = config.map(&[0.0, 0.0, 1.0, 0.0, -1.0, -1.0], 0.5);
+    assertClose(input.look_x, config.look_speed * 0.5);
+    assertClose(input.up, 0.0);
+}
+
+#[test]
+fn missing_axes_are_centered() {
+    let input = GamepadConfig::default().map(&[0.8, 0.0], 1.0);
+    assert!(input.right > 0.0);
+    assert_eq!((input.forward, input.up, input.look_x, input.look_y), (0.0, 0.0, 0.0, 0.0));
+}
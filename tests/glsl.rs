@@ -0,0 +1,447 @@
+#![allow(non_snake_case)]
+//! Offline checks of the bundled shaders, without a GL context:
+//! - every `.vs`/`.fs`/`.gs` file under `src/*/shaders/` parses (after resolving `#include`s)
+//! - for every pair of shaders loaded together (`Shader::new`, `Shader::with_geometry_shader`,
+//!   `ShaderBuilder`), the inputs of each stage are declared as outputs of the previous one with the same type
+//! - every uniform name set from Rust with `c_str!` is declared in the program of the `Shader` variable
+//!   it is set on, or in one of the shaders loaded by that file if the variable can't be resolved
+
+extern crate glsl;
+
+#[path = "../src/preprocessor.rs"]
+mod preprocessor;
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use glsl::parser::Parse;
+use glsl::syntax::{ArraySpecifier, Declaration, ExternalDeclaration, StorageQualifier, StructFieldSpecifier,
+                   TranslationUnit, TypeQualifier, TypeQualifierSpec, TypeSpecifierNonArray};
+
+const SHADER_EXTENSIONS: &[&str] = &["vs", "fs", "gs"];
+
+#[test]
+fn all_shaders_parse() {
+    let mut errors = Vec::new();
+    for path in files(Path::new("src"), &|path| inShaderDirectory(path) && hasExtension(path, SHADER_EXTENSIONS)) {
+        if let Err(err) = parse(&path.to_string_lossy()) {
+            errors.push(err);
+        }
+    }
+    assert!(errors.is_empty(), "\n{}", errors.join("\n"));
+}
+
+#[test]
+fn stage_interfaces_match() {
+    let mut errors = Vec::new();
+    for program in programs() {
+        let stages: Vec<(&str, TranslationUnit)> = match program.stages.iter()
+            .map(|path| parse(path).map(|unit| (path.as_str(), unit)))
+            .collect::<Result<_, _>>() {
+            Ok(stages) => stages,
+            Err(_) => continue, // reported by all_shaders_parse
+        };
+        for pair in stages.windows(2) {
+            let (producerPath, ref producer) = pair[0];
+            let (consumerPath, ref consumer) = pair[1];
+            // geometry shader inputs are arrays with one element per vertex of the primitive
+            let consumerIsGeometry = consumerPath.ends_with(".gs");
+            for mismatch in matchInterfaces(&interface(producer, StorageQualifier::Out),
+                                            &interface(consumer, StorageQualifier::In),
+                                            consumerIsGeometry) {
+                errors.push(format!("{}: {} -> {}: {}", program.source, producerPath, consumerPath, mismatch));
+            }
+        }
+    }
+    assert!(errors.is_empty(), "\n{}", errors.join("\n"));
+}
+
+#[test]
+fn uniforms_set_from_rust_are_declared() {
+    let mut programsBySource: HashMap<String, Vec<Program>> = HashMap::new();
+    for program in programs() {
+        programsBySource.entry(program.source.clone()).or_insert_with(Vec::new).push(program);
+    }
+
+    let mut uniformsByShader: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut errors = Vec::new();
+    for (source, programs) in &programsBySource {
+        let code = fs::read_to_string(source).unwrap();
+        for (position, args) in callArguments(&code, "c_str!(") {
+            // the programs bound to the receiving variable in this function, else all programs of the file
+            let receiver = uniformReceiver(&code, position);
+            let function = enclosingFunction(&code, position);
+            let bound: Vec<&Program> = programs.iter()
+                .filter(|program| receiver.is_some() && program.binding == receiver && program.function == function)
+                .collect();
+            let candidates = if bound.is_empty() { programs.iter().collect() } else { bound };
+            let mut shaders: Vec<&str> = candidates.iter().flat_map(|program| program.stages.iter().map(String::as_str)).collect();
+            shaders.sort();
+            shaders.dedup();
+
+            let mut declared = HashSet::new();
+            for shader in &shaders {
+                let uniforms = uniformsByShader.entry(shader.to_string())
+                    // parse errors are reported by all_shaders_parse
+                    .or_insert_with(|| parse(shader).map(|unit| uniformNames(&unit)).unwrap_or_default());
+                declared.extend(uniforms.iter().cloned());
+            }
+            for name in stringLiterals(args) {
+                if !declared.contains(&canonicalUniformName(&name)) {
+                    let on = receiver.as_ref().map(|receiver| format!(" (set on '{}')", receiver)).unwrap_or_default();
+                    errors.push(format!("{}: uniform '{}'{} isn't declared in any of {}", source, name, on, shaders.join(", ")));
+                }
+            }
+        }
+    }
+    errors.sort();
+    assert!(errors.is_empty(), "\n{}", errors.join("\n"));
+}
+
+/// the stages of a program loaded in a Rust file, in pipeline order
+struct Program {
+    source: String,
+    stages: Vec<String>,
+    /// the variable the program is assigned to with `let`, if any
+    binding: Option<String>,
+    /// the position of the function that loads it, see `enclosingFunction`
+    function: usize,
+}
+
+/// all programs loaded with literal paths in the Rust sources
+fn programs() -> Vec<Program> {
+    let mut programs = Vec::new();
+    for path in files(Path::new("src"), &|path| hasExtension(path, &["rs"])) {
+        let source = path.to_string_lossy().into_owned();
+        let code = fs::read_to_string(&path).unwrap();
+
+        // (vertex, fragment), also matches ReloadableShader::new and Shader::try_new
+        let program = |position: usize, stages: Vec<String>| Program {
+            source: source.clone(),
+            stages,
+            binding: letBinding(&code, position),
+            function: enclosingFunction(&code, position),
+        };
+        for (position, args) in callArguments(&code, "Shader::new(").into_iter().chain(callArguments(&code, "Shader::try_new(")) {
+            if let [ref vertex, ref fragment] = stringLiterals(args)[..] {
+                programs.push(program(position, vec![vertex.clone(), fragment.clone()]));
+            }
+        }
+        // (vertex, fragment, geometry)
+        for (position, args) in callArguments(&code, "with_geometry_shader(") {
+            if let [ref vertex, ref fragment, ref geometry] = stringLiterals(args)[..] {
+                programs.push(program(position, vec![vertex.clone(), geometry.clone(), fragment.clone()]));
+            }
+        }
+        // ShaderBuilder::new().vertex_file(..).fragment_file(..) ... ;
+        for (start, _) in code.match_indices("ShaderBuilder::new()") {
+            let chain = &code[start..start + code[start..].find(';').unwrap_or(code.len() - start)];
+            let stages: Vec<String> = ["vertex_file(", "geometry_file(", "fragment_file("].iter()
+                .filter_map(|method| callArguments(chain, method).first().and_then(|&(_, args)| stringLiterals(args).pop()))
+                .collect();
+            if stages.len() >= 2 {
+                programs.push(program(start, stages));
+            }
+        }
+    }
+    programs
+}
+
+fn parse(path: &str) -> Result<TranslationUnit, String> {
+    let source = preprocessor::preprocess(path).map_err(|err| err.to_string())?;
+    TranslationUnit::parse(stripDirectives(&source.code))
+        .map_err(|err| format!("{}: {}", path, err.info.trim()))
+}
+
+/// The parser only understands preprocessor directives between declarations and doesn't evaluate
+/// conditionals, so everything but `#version`/`#extension` is blanked out (keeping line numbers).
+/// With `#ifdef`s this checks both branches, which is good enough for the shaders in this repo.
+fn stripDirectives(code: &str) -> String {
+    code.lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('#') && !trimmed.starts_with("#version") && !trimmed.starts_with("#extension") {
+                ""
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// an input/output variable or block member
+#[derive(Debug, Clone, PartialEq)]
+struct Variable {
+    ty: TypeSpecifierNonArray,
+    array: Option<ArraySpecifier>,
+}
+
+/// the `in` or `out` variables and interface blocks of a stage; blocks are matched by block name
+#[derive(Default)]
+struct Interface {
+    variables: HashMap<String, Variable>,
+    blocks: HashMap<String, Vec<(String, Variable)>>,
+}
+
+fn interface(unit: &TranslationUnit, storage: StorageQualifier) -> Interface {
+    let mut interface = Interface::default();
+    for declaration in declarations(unit) {
+        match *declaration {
+            Declaration::InitDeclaratorList(ref list) => {
+                if !hasStorage(&list.head.ty.qualifier, &storage) {
+                    continue;
+                }
+                let ty = &list.head.ty.ty;
+                if let Some(ref name) = list.head.name {
+                    let array = list.head.array_specifier.clone().or_else(|| ty.array_specifier.clone());
+                    interface.variables.insert(name.0.clone(), Variable { ty: ty.ty.clone(), array });
+                }
+                for other in &list.tail {
+                    let array = other.ident.array_spec.clone().or_else(|| ty.array_specifier.clone());
+                    interface.variables.insert(other.ident.ident.0.clone(), Variable { ty: ty.ty.clone(), array });
+                }
+            }
+            Declaration::Block(ref block) if hasStorage(&Some(block.qualifier.clone()), &storage) => {
+                interface.blocks.insert(block.name.0.clone(), fieldVariables(&block.fields));
+            }
+            _ => {}
+        }
+    }
+    interface
+}
+
+fn matchInterfaces(producer: &Interface, consumer: &Interface, consumerIsGeometry: bool) -> Vec<String> {
+    let mut mismatches = Vec::new();
+    for (name, input) in &consumer.variables {
+        match producer.variables.get(name) {
+            None => mismatches.push(format!("input '{}' isn't an output of the previous stage", name)),
+            Some(output) => {
+                let matches = if consumerIsGeometry { output.ty == input.ty } else { output == input };
+                if !matches {
+                    mismatches.push(format!("'{}' is declared as {:?} but the previous stage writes {:?}", name, input, output));
+                }
+            }
+        }
+    }
+    for (name, inputs) in &consumer.blocks {
+        match producer.blocks.get(name) {
+            None => mismatches.push(format!("input block '{}' isn't an output of the previous stage", name)),
+            Some(outputs) if outputs != inputs =>
+                mismatches.push(format!("members of block '{}' differ: {:?} vs {:?}", name, inputs, outputs)),
+            _ => {}
+        }
+    }
+    mismatches.sort();
+    mismatches
+}
+
+/// All names a uniform can be set with from Rust, with array indices replaced by `[]`
+/// (see `canonicalUniformName`): `material`, `material.diffuse`, `lights`, `lights[]`,
+/// `lights[].position`, ..., and the names of uniform blocks for `UniformBuffer::bind_block`.
+fn uniformNames(unit: &TranslationUnit) -> HashSet<String> {
+    let mut structs: HashMap<String, Vec<(String, Variable)>> = HashMap::new();
+    let mut names = HashSet::new();
+    for declaration in declarations(unit) {
+        match *declaration {
+            Declaration::InitDeclaratorList(ref list) => {
+                if let TypeSpecifierNonArray::Struct(ref specifier) = list.head.ty.ty.ty {
+                    if let Some(ref name) = specifier.name {
+                        structs.insert(name.0.clone(), fieldVariables(&specifier.fields.0));
+                    }
+                }
+                if !hasStorage(&list.head.ty.qualifier, &StorageQualifier::Uniform) {
+                    continue;
+                }
+                let ty = &list.head.ty.ty;
+                if let Some(ref name) = list.head.name {
+                    let array = list.head.array_specifier.clone().or_else(|| ty.array_specifier.clone());
+                    addUniformNames(&mut names, &structs, name.0.clone(), &Variable { ty: ty.ty.clone(), array });
+                }
+                for other in &list.tail {
+                    let array = other.ident.array_spec.clone().or_else(|| ty.array_specifier.clone());
+                    addUniformNames(&mut names, &structs, other.ident.ident.0.clone(), &Variable { ty: ty.ty.clone(), array });
+                }
+            }
+            Declaration::Block(ref block) if hasStorage(&Some(block.qualifier.clone()), &StorageQualifier::Uniform) => {
+                names.insert(block.name.0.clone());
+                let prefix = block.identifier.as_ref().map(|_| format!("{}.", block.name.0)).unwrap_or_default();
+                for (member, variable) in fieldVariables(&block.fields) {
+                    addUniformNames(&mut names, &structs, prefix.clone() + &member, &variable);
+                }
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+fn addUniformNames(names: &mut HashSet<String>, structs: &HashMap<String, Vec<(String, Variable)>>,
+                   name: String, variable: &Variable) {
+    let element = if variable.array.is_some() {
+        names.insert(name.clone());
+        name + "[]"
+    } else {
+        name
+    };
+    names.insert(element.clone());
+    if let TypeSpecifierNonArray::TypeName(ref typeName) = variable.ty {
+        if let Some(fields) = structs.get(&typeName.0) {
+            for (field, fieldVariable) in fields {
+                addUniformNames(names, structs, format!("{}.{}", element, field), fieldVariable);
+            }
+        }
+    }
+}
+
+/// `pointLights[0].position` -> `pointLights[].position`
+fn canonicalUniformName(name: &str) -> String {
+    let mut canonical = String::with_capacity(name.len());
+    let mut inIndex = false;
+    for c in name.chars() {
+        match c {
+            '[' => { inIndex = true; canonical.push(c) }
+            ']' => { inIndex = false; canonical.push(c) }
+            _ if inIndex => {}
+            _ => canonical.push(c),
+        }
+    }
+    canonical
+}
+
+fn declarations(unit: &TranslationUnit) -> Vec<&Declaration> {
+    (unit.0).0.iter()
+        .filter_map(|external| match *external {
+            ExternalDeclaration::Declaration(ref declaration) => Some(declaration),
+            _ => None,
+        })
+        .collect()
+}
+
+fn fieldVariables(fields: &[StructFieldSpecifier]) -> Vec<(String, Variable)> {
+    let mut variables = Vec::new();
+    for field in fields {
+        for identifier in &field.identifiers.0 {
+            let array = identifier.array_spec.clone().or_else(|| field.ty.array_specifier.clone());
+            variables.push((identifier.ident.0.clone(), Variable { ty: field.ty.ty.clone(), array }));
+        }
+    }
+    variables
+}
+
+fn hasStorage(qualifier: &Option<TypeQualifier>, storage: &StorageQualifier) -> bool {
+    qualifier.as_ref().map_or(false, |qualifier| {
+        qualifier.qualifiers.0.iter().any(|spec| *spec == TypeQualifierSpec::Storage(storage.clone()))
+    })
+}
+
+/// The position and the text between the parentheses of every call of `function` (which has to end with `(`)
+fn callArguments<'a>(code: &'a str, function: &str) -> Vec<(usize, &'a str)> {
+    let mut calls = Vec::new();
+    for (position, _) in code.match_indices(function) {
+        let start = position + function.len();
+        let mut depth = 1;
+        for (i, c) in code[start..].char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                calls.push((position, &code[start..start + i]));
+                break;
+            }
+        }
+    }
+    calls
+}
+
+/// `name` if the statement that contains `position` starts with `let name =` (or `let mut name =`)
+fn letBinding(code: &str, position: usize) -> Option<String> {
+    let statementStart = code[..position].rfind(|c| c == ';' || c == '{' || c == '}').map_or(0, |i| i + 1);
+    let statement = code[statementStart..position].trim_start();
+    if !statement.starts_with("let ") {
+        return None;
+    }
+    let pattern = statement["let ".len()..].trim_start();
+    let pattern = if pattern.starts_with("mut ") { pattern["mut ".len()..].trim_start() } else { pattern };
+    let name = &pattern[..pattern.find(|c: char| !isIdentifierChar(c)).unwrap_or(pattern.len())];
+    let rest = pattern[name.len()..].trim_start();
+    if !name.is_empty() && (rest.starts_with('=') || rest.starts_with(':')) { Some(name.to_string()) } else { None }
+}
+
+/// The `Shader` variable a uniform is set on: `shader` in `shader.setMat4(c_str!(..)`,
+/// `GetUniformLocation(shader.ID, c_str!(..)` or `bind_block(&shader, c_str!(..)`, where `position`
+/// is the position of the `c_str!`. `None` for anything else, e.g. `self.shader.setMat4(..)`.
+fn uniformReceiver(code: &str, position: usize) -> Option<String> {
+    let before = code[..position].trim_end();
+    let receiver = if before.ends_with(',') {
+        // the previous argument
+        let argument = before[before.rfind('(')? + 1..before.len() - 1].trim();
+        let argument = argument.trim_start_matches('&');
+        if argument.ends_with(".ID") { &argument[..argument.len() - ".ID".len()] } else { argument }
+    } else if before.ends_with('(') {
+        // the method's receiver
+        let before = before[..before.len() - 1].trim_end();
+        let method = trailingIdentifier(before);
+        let before = before[..before.len() - method.len()].trim_end();
+        if method.is_empty() || !before.ends_with('.') {
+            return None;
+        }
+        let before = before[..before.len() - 1].trim_end();
+        let receiver = trailingIdentifier(before);
+        if before[..before.len() - receiver.len()].ends_with('.') {
+            return None;
+        }
+        receiver
+    } else {
+        return None;
+    };
+    if !receiver.is_empty() && receiver.chars().all(isIdentifierChar) { Some(receiver.to_string()) } else { None }
+}
+
+/// the position of the `fn` that `position` is in (0 outside of functions), to tell variables of
+/// the same name in different functions apart
+fn enclosingFunction(code: &str, position: usize) -> usize {
+    code[..position].match_indices("fn ")
+        .map(|(i, _)| i)
+        .filter(|&i| !code[..i].ends_with(isIdentifierChar))
+        .last()
+        .unwrap_or(0)
+}
+
+fn trailingIdentifier(code: &str) -> &str {
+    &code[code.rfind(|c: char| !isIdentifierChar(c)).map_or(0, |i| i + 1)..]
+}
+
+fn isIdentifierChar(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// string literals without escapes, which is all shader paths and uniform names use
+fn stringLiterals(code: &str) -> Vec<String> {
+    code.split('"').skip(1).step_by(2).map(String::from).collect()
+}
+
+fn files(directory: &Path, filter: &dyn Fn(&Path) -> bool) -> Vec<PathBuf> {
+    let mut result = Vec::new();
+    for entry in fs::read_dir(directory).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            result.extend(files(&path, filter));
+        } else if filter(&path) {
+            result.push(path);
+        }
+    }
+    result.sort();
+    result
+}
+
+fn inShaderDirectory(path: &Path) -> bool {
+    path.parent().and_then(Path::file_name).map_or(false, |name| name == "shaders")
+}
+
+fn hasExtension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension().and_then(|ext| ext.to_str()).map_or(false, |ext| extensions.contains(&ext))
+}
@@ -0,0 +1,61 @@
+#![allow(non_snake_case)]
+//! Input bindings and config files, without a window
+
+extern crate glfw;
+
+#[path = "../src/input.rs"]
+mod input;
+
+use glfw::{Key, MouseButton};
+
+use input::{Binding, InputConfigError, InputMap};
+
+#[test]
+fn default_bindings() {
+    let map = InputMap::default();
+    assert_eq!(map.bindings["toggle_hdr"], vec![Binding::Key(Key::Space)]);
+    assert_eq!(map.bindings["exposure_down"], vec![Binding::Key(Key::Q)]);
+    // only actions that no tutorial queries together share a key
+    assert_eq!(map.conflicts(), vec![
+        (Binding::Key(Key::Q), vec!["exposure_down".to_string(), "roll_left".to_string()]),
+        (Binding::Key(Key::E), vec!["exposure_up".to_string(), "roll_right".to_string()]),
+        (Binding::Key(Key::Space), vec!["toggle_gamma".to_string(), "toggle_hdr".to_string()]),
+    ]);
+}
+
+#[test]
+fn config_rebinds_actions() {
+    let mut map = InputMap::default();
+    map.apply("# AZERTY\nForward = z, up\n\nzoom_in = ScrollUp, KpAdd # comment\ntoggle_blinn =\n").unwrap();
+    assert_eq!(map.bindings["forward"], vec![Binding::Key(Key::Z), Binding::Key(Key::Up)]);
+    assert_eq!(map.bindings["zoom_in"], vec![Binding::ScrollUp, Binding::Key(Key::KpAdd)]);
+    assert_eq!(map.bindings["toggle_blinn"], vec![]);
+    // untouched
+    assert_eq!(map.bindings["backward"], vec![Binding::Key(Key::S)]);
+}
+
+#[test]
+fn conflicts_list_all_actions_of_a_binding() {
+    let mut map = InputMap::default();
+    map.apply("toggle_hdr = MouseRight\nexposure_up = Q\nroll_right =").unwrap();
+    let conflicts = map.conflicts();
+    assert_eq!(conflicts.len(), 2, "{:?}", conflicts);
+    assert!(conflicts.contains(&(Binding::Key(Key::Q),
+                                 vec!["exposure_down".to_string(), "exposure_up".to_string(), "roll_left".to_string()])));
+    assert!(conflicts.contains(&(Binding::MouseButton(MouseButton::Button2),
+                                 vec!["camera_button_right".to_string(), "toggle_hdr".to_string()])));
+    assert_eq!(Binding::Key(Key::Q).to_string(), "Q");
+    assert_eq!(Binding::MouseButton(MouseButton::Button2).to_string(), "MouseRight");
+}
+
+#[test]
+fn invalid_config_reports_line_number() {
+    let mut map = InputMap::default();
+    match map.apply("forward = W\n\nleft = NoSuchKey") {
+        Err(InputConfigError::Parse { line, ref message }) => {
+            assert_eq!(line, 3);
+            assert!(message.contains("NoSuchKey"), "{}", message);
+        }
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+}
@@ -0,0 +1,260 @@
+#![allow(non_snake_case)]
+//! Importing OBJ and glTF files into `ModelData`, without a GL context
+
+extern crate cgmath;
+#[cfg(feature = "gltf")]
+extern crate gltf;
+extern crate tobj;
+
+mod common;
+
+#[path = "../src/bounds.rs"]
+mod bounds;
+#[path = "../src/model_data.rs"]
+mod model_data;
+#[path = "../src/normals.rs"]
+mod normals;
+#[path = "../src/tangents.rs"]
+mod tangents;
+
+use std::path::PathBuf;
+
+use cgmath::{Deg, Point3};
+#[cfg(feature = "gltf")]
+use cgmath::{vec3, Matrix3, Vector3};
+use cgmath::prelude::*;
+
+use bounds::BoundingSphere;
+use model_data::{LoadOptions, ModelData, ModelError, TextureDescriptor, TextureSource};
+
+use common::{assertClose, writeFiles};
+
+fn load(directory: &PathBuf, file: &str, options: &LoadOptions) -> Result<ModelData, ModelError> {
+    ModelData::load(directory.join(file).to_str().unwrap(), options)
+}
+
+const CUBE: &str = "
+v -1 -1 -1
+v 1 -1 -1
+v 1 1 -1
+v -1 1 -1
+v -1 -1 1
+v 1 -1 1
+v 1 1 1
+v -1 1 1
+f 1 3 2
+f 1 4 3
+f 5 6 7
+f 5 7 8
+f 1 2 6
+f 1 6 5
+f 4 7 3
+f 4 8 7
+f 1 5 8
+f 1 8 4
+f 2 3 7
+f 2 7 6
+";
+
+#[test]
+fn obj_without_normals_and_texcoords() {
+    let directory = writeFiles("obj-cube", &[("cube.obj", CUBE)]);
+    let data = load(&directory, "cube.obj", &LoadOptions::default()).unwrap();
+    assert_eq!(data.meshes.len(), 1);
+    let mesh = &data.meshes[0];
+    // the 90° edges are sharper than the default crease angle, so every face has its own vertices
+    assert_eq!(mesh.vertices.len(), 24);
+    assert_eq!(mesh.indices.len(), 36);
+    for triangle in mesh.indices.chunks(3) {
+        let v: Vec<_> = triangle.iter().map(|&i| mesh.vertices[i as usize]).collect();
+        let faceNormal = (v[1].Position - v[0].Position).cross(v[2].Position - v[0].Position).normalize();
+        for vertex in &v {
+            assertClose(vertex.Normal, faceNormal);
+            assert_eq!(vertex.TexCoords, cgmath::vec2(0.0, 0.0));
+            assert!(vertex.Tangent.dot(vertex.Normal).abs() < 1e-5);
+        }
+        // outward facing
+        assert!(faceNormal.dot(v[0].Position) > 0.0);
+    }
+
+    let smooth = load(&directory, "cube.obj", &LoadOptions { crease_angle: Deg(180.0) }).unwrap();
+    assert_eq!(smooth.meshes[0].vertices.len(), 8);
+}
+
+#[test]
+fn bounds() {
+    let directory = writeFiles("obj-bounds", &[("cube.obj", CUBE)]);
+    let data = load(&directory, "cube.obj", &LoadOptions::default()).unwrap();
+    let aabb = data.aabb();
+    assert_eq!(aabb, data.meshes[0].aabb());
+    assert_eq!((aabb.min, aabb.max), (Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0)));
+    let sphere = data.bounding_sphere();
+    assert_eq!(sphere.center, Point3::new(0.0, 0.0, 0.0));
+    assert!((sphere.radius - 3f32.sqrt()).abs() < 1e-5);
+
+    assert_eq!(ModelData::default().bounding_sphere(), BoundingSphere::default());
+    assert!(ModelData::default().aabb().is_empty());
+}
+
+#[test]
+fn obj_empty_shapes_are_skipped() {
+    let obj = format!("o empty\nv 0 0 0\no cube\n{}", CUBE);
+    let directory = writeFiles("obj-empty", &[("model.obj", &obj)]);
+    let data = load(&directory, "model.obj", &LoadOptions::default()).unwrap();
+    assert_eq!(data.meshes.len(), 1);
+    assert_eq!(data.meshes[0].indices.len(), 36);
+}
+
+#[test]
+fn obj_material_textures() {
+    let obj = format!("mtllib cube.mtl\nusemtl metal\n{}", CUBE);
+    let mtl = "newmtl metal\nmap_Kd diffuse.png\nmap_Ks specular.png\n";
+    let directory = writeFiles("obj-material", &[("cube.obj", &obj), ("cube.mtl", mtl)]);
+    let data = load(&directory, "cube.obj", &LoadOptions::default()).unwrap();
+    assert_eq!(data.directory, directory.to_str().unwrap());
+    assert_eq!(data.meshes[0].textures, vec![
+        TextureDescriptor { type_: "texture_diffuse".into(), source: TextureSource::File("diffuse.png".into()) },
+        TextureDescriptor { type_: "texture_specular".into(), source: TextureSource::File("specular.png".into()) },
+    ]);
+}
+
+#[test]
+fn missing_file_is_an_error() {
+    let directory = writeFiles("obj-missing", &[]);
+    match load(&directory, "missing.obj", &LoadOptions::default()) {
+        Err(ModelError::Obj(_)) => {}
+        other => panic!("expected an OBJ error, got {:?}", other.map(|data| data.meshes.len())),
+    }
+}
+
+#[cfg(feature = "gltf")]
+/// a glTF file with one triangle (0, 0, 0), (1, 0, 0), (0, 1, 0) without normals, in a node with
+/// `scale` that is the child of a node moved to z = 5
+fn triangleGltf(scale: [f32; 3]) -> String {
+    format!(r#"{{
+        "asset": {{ "version": "2.0" }},
+        "scene": 0,
+        "scenes": [{{ "nodes": [0] }}],
+        "nodes": [
+            {{ "translation": [0, 0, 5], "children": [1] }},
+            {{ "scale": [{}, {}, {}], "mesh": 0 }}
+        ],
+        "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 0 }}, "material": 0 }}] }}],
+        "materials": [{{ "pbrMetallicRoughness": {{ "baseColorFactor": [1, 0, 0, 1], "metallicFactor": 0.5 }} }}],
+        "accessors": [{{
+            "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+            "min": [0, 0, 0], "max": [1, 1, 0]
+        }}],
+        "bufferViews": [{{ "buffer": 0, "byteLength": 36 }}],
+        "buffers": [{{
+            "byteLength": 36,
+            "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAA"
+        }}]
+    }}"#, scale[0], scale[1], scale[2])
+}
+
+#[cfg(feature = "gltf")]
+#[test]
+fn gltf_bounds() {
+    // the sphere around the box corners would be larger than the one around the triangle
+    let directory = writeFiles("gltf-bounds", &[("triangle.gltf", &triangleGltf([2.0, 2.0, 2.0]))]);
+    let data = load(&directory, "triangle.gltf", &LoadOptions::default()).unwrap();
+    let sphere = data.bounding_sphere();
+    assert_eq!(sphere.center, Point3::new(1.0, 1.0, 5.0));
+    assert!((sphere.radius - 2f32.sqrt()).abs() < 1e-5);
+    assert!(sphere.radius < BoundingSphere::from_aabb(&data.aabb()).radius + 1e-5);
+}
+
+#[cfg(not(feature = "gltf"))]
+#[test]
+fn gltf_needs_the_feature() {
+    let directory = writeFiles("gltf-disabled", &[("triangle.gltf", "{}")]);
+    match load(&directory, "triangle.gltf", &LoadOptions::default()) {
+        Err(ModelError::GltfDisabled) => {}
+        other => panic!("expected GltfDisabled, got {:?}", other.map(|data| data.meshes.len())),
+    }
+}
+
+#[cfg(feature = "gltf")]
+#[test]
+fn gltf_node_transforms_and_material() {
+    let directory = writeFiles("gltf-nodes", &[("triangle.gltf", &triangleGltf([2.0, 2.0, 2.0]))]);
+    let data = load(&directory, "triangle.gltf", &LoadOptions::default()).unwrap();
+    assert_eq!(data.meshes.len(), 1);
+    let mesh = &data.meshes[0];
+    let positions: Vec<_> = mesh.indices.iter().map(|&i| mesh.vertices[i as usize].Position).collect();
+    assert_eq!(positions, vec![vec3(0.0, 0.0, 5.0), vec3(2.0, 0.0, 5.0), vec3(0.0, 2.0, 5.0)]);
+    for vertex in &mesh.vertices {
+        // flat normals, as the spec requires without a NORMAL attribute
+        assertClose(vertex.Normal, Vector3::unit_z());
+    }
+    assert_eq!(mesh.material.base_color_factor, cgmath::vec4(1.0, 0.0, 0.0, 1.0));
+    assert_eq!(mesh.material.metallic_factor, 0.5);
+    assert_eq!(mesh.material.roughness_factor, 1.0);
+    assert!(mesh.textures.is_empty());
+}
+
+#[cfg(feature = "gltf")]
+#[test]
+fn gltf_mirrored_node_keeps_front_faces() {
+    let directory = writeFiles("gltf-mirrored", &[("triangle.gltf", &triangleGltf([-1.0, 1.0, 1.0]))]);
+    let data = load(&directory, "triangle.gltf", &LoadOptions::default()).unwrap();
+    let mesh = &data.meshes[0];
+    let v: Vec<_> = mesh.indices.iter().map(|&i| mesh.vertices[i as usize]).collect();
+    // counter-clockwise seen from the side the normal points to
+    let windingNormal = (v[1].Position - v[0].Position).cross(v[2].Position - v[0].Position).normalize();
+    assertClose(windingNormal, v[0].Normal);
+}
+
+#[cfg(feature = "gltf")]
+/// a glTF file with the triangle of `triangleGltf` facing +Z in a node with `scale`, with a
+/// diagonal tangent (0.6, 0.8, 0) and a left-handed bitangent (w = -1)
+fn tangentGltf(scale: [f32; 3]) -> String {
+    format!(r#"{{
+        "asset": {{ "version": "2.0" }},
+        "scenes": [{{ "nodes": [0] }}],
+        "nodes": [{{ "scale": [{}, {}, {}], "mesh": 0 }}],
+        "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 0, "NORMAL": 1, "TANGENT": 2 }} }}] }}],
+        "accessors": [
+            {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0] }},
+            {{ "bufferView": 0, "byteOffset": 36, "componentType": 5126, "count": 3, "type": "VEC3" }},
+            {{ "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC4" }}
+        ],
+        "bufferViews": [
+            {{ "buffer": 0, "byteLength": 72 }},
+            {{ "buffer": 0, "byteOffset": 72, "byteLength": 48 }}
+        ],
+        "buffers": [{{
+            "byteLength": 120,
+            "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/mpkZP83MTD8AAAAAAACAv5qZGT/NzEw/AAAAAAAAgL+amRk/zcxMPwAAAAAAAIC/"
+        }}]
+    }}"#, scale[0], scale[1], scale[2])
+}
+
+#[cfg(feature = "gltf")]
+#[test]
+fn gltf_tangent_frames_stay_orthonormal() {
+    for &(name, scale) in &[("gltf-tangents", [2.0, 1.0, 1.0]), ("gltf-tangents-mirrored", [-2.0, 1.0, 1.0])] {
+        let directory = writeFiles(name, &[("triangle.gltf", &tangentGltf(scale))]);
+        let data = load(&directory, "triangle.gltf", &LoadOptions::default()).unwrap();
+        let linear = Matrix3::from_diagonal(Vector3::from(scale));
+        for vertex in &data.meshes[0].vertices {
+            let (n, t, b) = (vertex.Normal, vertex.Tangent, vertex.Bitangent);
+            assertClose(n, Vector3::unit_z());
+            // the tangent follows the scaled surface
+            assertClose(t, (linear * vec3(0.6, 0.8, 0.0)).normalize());
+            assert!(t.dot(n).abs() < 1e-5 && b.dot(n).abs() < 1e-5 && b.dot(t).abs() < 1e-5, "{:?} isn't orthogonal", vertex);
+            assert!((b.magnitude() - 1.0).abs() < 1e-5);
+            // the bitangent still points to the side the file's bitangent is scaled to
+            assert!(b.dot(linear * vec3(0.8, -0.6, 0.0)) > 0.0, "{:?} has the wrong handedness", vertex);
+        }
+    }
+}
+
+#[cfg(feature = "gltf")]
+#[test]
+fn gltf_singular_transforms_are_skipped() {
+    let directory = writeFiles("gltf-singular", &[("triangle.gltf", &triangleGltf([0.0, 1.0, 1.0]))]);
+    let data = load(&directory, "triangle.gltf", &LoadOptions::default()).unwrap();
+    assert!(data.meshes.is_empty());
+}
@@ -0,0 +1,84 @@
+#![allow(non_snake_case)]
+//! Normal generation with a crease angle
+
+extern crate cgmath;
+
+mod common;
+
+#[path = "../src/normals.rs"]
+mod normals;
+
+use cgmath::{vec3, Deg, Vector3};
+use cgmath::prelude::*;
+
+use normals::generate_normals;
+
+use common::assertClose;
+
+/// two quads meeting at a 90° edge along the z axis: one facing +Y, one facing +X
+fn corner() -> (Vec<Vector3<f32>>, Vec<u32>) {
+    let positions = vec![
+        vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, 1.0), vec3(-1.0, 0.0, 1.0), vec3(-1.0, 0.0, 0.0),
+        vec3(0.0, -1.0, 0.0), vec3(0.0, -1.0, 1.0),
+    ];
+    let indices = vec![0, 3, 2, 0, 2, 1, 0, 1, 5, 0, 5, 4];
+    (positions, indices)
+}
+
+#[test]
+fn flat_plane_stays_indexed() {
+    let positions = vec![vec3(0.0, 0.0, 0.0), vec3(1.0, 0.0, 0.0), vec3(1.0, 1.0, 0.0), vec3(0.0, 1.0, 0.0)];
+    let generated = generate_normals(&positions, &[0, 1, 2, 0, 2, 3], Deg(0.0));
+    assert_eq!(generated.vertices, vec![0, 1, 2, 3]);
+    assert_eq!(generated.indices, vec![0, 1, 2, 0, 2, 3]);
+    for &normal in &generated.normals {
+        assertClose(normal, Vector3::unit_z());
+    }
+}
+
+#[test]
+fn sharp_edge_is_split() {
+    let (positions, indices) = corner();
+    let generated = generate_normals(&positions, &indices, Deg(60.0));
+    // the two vertices on the edge exist once per side
+    assert_eq!(generated.vertices.len(), 8);
+    for (triangle, expected) in generated.indices.chunks(3).zip(&[Vector3::unit_y(), Vector3::unit_y(), Vector3::unit_x(), Vector3::unit_x()]) {
+        for &i in triangle {
+            assertClose(generated.normals[i as usize], *expected);
+        }
+    }
+}
+
+#[test]
+fn smooth_edge_is_shared() {
+    let (positions, indices) = corner();
+    let generated = generate_normals(&positions, &indices, Deg(120.0));
+    assert_eq!(generated.vertices.len(), 6);
+    let diagonal = vec3(1.0, 1.0, 0.0).normalize();
+    for (i, &vertex) in generated.vertices.iter().enumerate() {
+        let expected = match vertex {
+            0 | 1 => diagonal,
+            2 | 3 => Vector3::unit_y(),
+            _ => Vector3::unit_x(),
+        };
+        assertClose(generated.normals[i], expected);
+    }
+}
+
+#[test]
+fn seams_with_separate_vertices_are_smoothed() {
+    // the same corner, but the quads don't share vertices (e.g. because of a UV seam)
+    let (mut positions, mut indices) = corner();
+    positions.push(positions[0]);
+    positions.push(positions[1]);
+    for i in indices.iter_mut().skip(6) {
+        *i = match *i { 0 => 6, 1 => 7, i => i };
+    }
+    let generated = generate_normals(&positions, &indices, Deg(120.0));
+    let diagonal = vec3(1.0, 1.0, 0.0).normalize();
+    for (i, &vertex) in generated.vertices.iter().enumerate() {
+        if vertex == 0 || vertex == 6 {
+            assertClose(generated.normals[i], diagonal);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+#![allow(non_snake_case)]
+//! Ray picking on the CPU, without a GL context
+
+extern crate cgmath;
+
+mod common;
+
+#[path = "../src/bounds.rs"]
+mod bounds;
+#[path = "../src/picking.rs"]
+mod picking;
+
+use cgmath::{perspective, vec3, Deg, Matrix4, Point3, Vector3};
+use cgmath::prelude::*;
+
+use bounds::Aabb;
+use picking::{screen_ray, ray_aabb, ray_triangle, ray_triangles, Ray};
+
+use common::assertClose;
+
+#[test]
+fn screen_ray_through_center_follows_view_direction() {
+    let view = Matrix4::look_at(Point3::new(0.0, 0.0, 3.0), Point3::new(0.0, 0.0, 0.0), Vector3::unit_y());
+    let projection = perspective(Deg(45.0), 800.0 / 600.0, 0.1, 100.0);
+    let ray = screen_ray(400.0, 300.0, 800.0, 600.0, &view, &projection);
+    assertClose(ray.origin.z, 2.9);
+    assertClose(ray.direction.z, -1.0);
+
+    // the top left corner of the window is up and to the left
+    let ray = screen_ray(0.0, 0.0, 800.0, 600.0, &view, &projection);
+    assert!(ray.direction.x < 0.0 && ray.direction.y > 0.0);
+}
+
+#[test]
+fn triangle_hits_and_misses() {
+    let (a, b, c) = (Point3::new(-1.0, -1.0, 0.0), Point3::new(1.0, -1.0, 0.0), Point3::new(0.0, 1.0, 0.0));
+    let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), vec3(0.0, 0.0, -1.0));
+    assertClose(ray_triangle(&ray, a, b, c).unwrap(), 5.0);
+    // back side
+    assertClose(ray_triangle(&Ray::new(Point3::new(0.0, 0.0, -2.0), vec3(0.0, 0.0, 1.0)), a, b, c).unwrap(), 2.0);
+    // pointing away, beside and parallel
+    assert_eq!(ray_triangle(&Ray::new(Point3::new(0.0, 0.0, 5.0), vec3(0.0, 0.0, 1.0)), a, b, c), None);
+    assert_eq!(ray_triangle(&Ray::new(Point3::new(2.0, 0.0, 5.0), vec3(0.0, 0.0, -1.0)), a, b, c), None);
+    assert_eq!(ray_triangle(&Ray::new(Point3::new(0.0, 0.0, 5.0), vec3(1.0, 0.0, 0.0)), a, b, c), None);
+}
+
+#[test]
+fn aabb_entry_distance() {
+    let aabb = Aabb { min: Point3::new(-1.0, -1.0, -1.0), max: Point3::new(1.0, 1.0, 1.0) };
+    assertClose(ray_aabb(&Ray::new(Point3::new(0.0, 0.0, 5.0), vec3(0.0, 0.0, -1.0)), &aabb).unwrap(), 4.0);
+    assertClose(ray_aabb(&Ray::new(Point3::new(0.5, 0.0, 0.0), vec3(0.0, 1.0, 0.0)), &aabb).unwrap(), 0.0);
+    assert_eq!(ray_aabb(&Ray::new(Point3::new(2.0, 0.0, 5.0), vec3(0.0, 0.0, -1.0)), &aabb), None);
+    assert_eq!(ray_aabb(&Ray::new(Point3::new(0.0, 0.0, 5.0), vec3(0.0, 0.0, 1.0)), &aabb), None);
+}
+
+#[test]
+fn closest_triangle_of_transformed_mesh() {
+    // two quads facing +Z, at z = 0 and z = -1
+    let positions = [
+        Point3::new(-1.0, -1.0, 0.0), Point3::new(1.0, -1.0, 0.0), Point3::new(1.0, 1.0, 0.0), Point3::new(-1.0, 1.0, 0.0),
+        Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, -1.0, -1.0), Point3::new(1.0, 1.0, -1.0), Point3::new(-1.0, 1.0, -1.0),
+    ];
+    let indices = [4, 5, 6, 4, 6, 7, 0, 1, 2, 0, 2, 3];
+    // the mesh is moved 10 units to the right
+    let model = Matrix4::from_translation(vec3(10.0, 0.0, 0.0));
+    let ray = Ray::new(Point3::new(10.5, 0.2, 5.0), vec3(0.0, 0.0, -1.0)).transform(&model.invert().unwrap());
+
+    let (triangle, distance) = ray_triangles(&ray, &indices, |i| positions[i as usize]).unwrap();
+    assert_eq!(triangle, 2);
+    assertClose(distance, 5.0);
+}
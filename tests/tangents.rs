@@ -0,0 +1,82 @@
+#![allow(non_snake_case)]
+//! Tangent frame generation on known geometry
+
+extern crate cgmath;
+
+mod common;
+
+#[path = "../src/tangents.rs"]
+mod tangents;
+
+use cgmath::{vec2, vec3, Vector2, Vector3};
+use cgmath::prelude::*;
+
+use tangents::{bitangent, generate_tangents};
+
+use common::assertClose;
+
+/// the quad of `renderQuad` in 5.4: facing +Z, UVs from (0, 0) bottom left to (1, 1) top right
+fn quad(texCoords: [Vector2<f32>; 4]) -> (Vec<Vector3<f32>>, Vec<Vector3<f32>>, Vec<Vector2<f32>>, Vec<u32>) {
+    let positions = vec![vec3(-1.0, 1.0, 0.0), vec3(-1.0, -1.0, 0.0), vec3(1.0, -1.0, 0.0), vec3(1.0, 1.0, 0.0)];
+    (positions, vec![Vector3::unit_z(); 4], texCoords.to_vec(), vec![0, 1, 2, 0, 2, 3])
+}
+
+#[test]
+fn quad_matches_normal_mapping_tutorial() {
+    let (p, n, t, i) = quad([vec2(0.0, 1.0), vec2(0.0, 0.0), vec2(1.0, 0.0), vec2(1.0, 1.0)]);
+    for (tangent, &normal) in generate_tangents(&p, &n, &t, &i).into_iter().zip(&n) {
+        assertClose(tangent.truncate(), Vector3::unit_x());
+        assert_eq!(tangent.w, 1.0);
+        assertClose(bitangent(normal, tangent), Vector3::unit_y());
+    }
+}
+
+#[test]
+fn mirrored_uvs_flip_handedness() {
+    // U runs from right to left
+    let (p, n, t, i) = quad([vec2(1.0, 1.0), vec2(1.0, 0.0), vec2(0.0, 0.0), vec2(0.0, 1.0)]);
+    for (tangent, &normal) in generate_tangents(&p, &n, &t, &i).into_iter().zip(&n) {
+        assertClose(tangent.truncate(), -Vector3::unit_x());
+        assert_eq!(tangent.w, -1.0);
+        // +V is still up
+        assertClose(bitangent(normal, tangent), Vector3::unit_y());
+    }
+}
+
+#[test]
+fn tangent_is_orthogonal_to_smoothed_normal() {
+    // a tilted normal, as on a smooth-shaded surface
+    let (p, _, t, i) = quad([vec2(0.0, 1.0), vec2(0.0, 0.0), vec2(1.0, 0.0), vec2(1.0, 1.0)]);
+    let n = vec![vec3(0.5, 0.0, 1.0).normalize(); 4];
+    for (tangent, &normal) in generate_tangents(&p, &n, &t, &i).into_iter().zip(&n) {
+        let t3 = tangent.truncate();
+        assert!(t3.dot(normal).abs() < 1e-5);
+        assert!((t3.magnitude() - 1.0).abs() < 1e-5);
+        // +X with the part along the normal removed
+        assertClose(t3, vec3(0.8, 0.0, -0.4).normalize());
+        assert_eq!(tangent.w, 1.0);
+    }
+}
+
+#[test]
+fn cube_side_faces() {
+    // the +X side of a cube, UVs wrapping around it: U along -Z, V along +Y
+    let positions = vec![vec3(1.0, -1.0, 1.0), vec3(1.0, -1.0, -1.0), vec3(1.0, 1.0, -1.0), vec3(1.0, 1.0, 1.0)];
+    let normals = vec![Vector3::unit_x(); 4];
+    let texCoords = vec![vec2(0.0, 0.0), vec2(1.0, 0.0), vec2(1.0, 1.0), vec2(0.0, 1.0)];
+    let tangents = generate_tangents(&positions, &normals, &texCoords, &[0, 1, 2, 0, 2, 3]);
+    for tangent in tangents {
+        assertClose(tangent.truncate(), -Vector3::unit_z());
+        assertClose(bitangent(Vector3::unit_x(), tangent), Vector3::unit_y());
+    }
+}
+
+#[test]
+fn degenerate_uvs_give_some_orthogonal_tangent() {
+    let (p, n, t, i) = quad([vec2(0.5, 0.5); 4]);
+    for tangent in generate_tangents(&p, &n, &t, &i) {
+        let t3 = tangent.truncate();
+        assert!((t3.magnitude() - 1.0).abs() < 1e-5);
+        assert!(t3.dot(Vector3::unit_z()).abs() < 1e-5);
+    }
+}
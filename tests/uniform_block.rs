@@ -0,0 +1,97 @@
+#![allow(non_snake_case)]
+//! std140 layouts computed by `uniform_block!`, without a GL context
+
+extern crate cgmath;
+extern crate gl;
+
+#[allow(unused_macros)]
+#[path = "../src/macros.rs"]
+mod macros;
+#[path = "../src/preprocessor.rs"]
+mod preprocessor;
+#[path = "../src/program_cache.rs"]
+mod program_cache;
+#[path = "../src/shader.rs"]
+mod shader;
+#[path = "../src/uniform.rs"]
+mod uniform;
+#[path = "../src/uniform_buffer.rs"]
+mod uniform_buffer;
+
+use cgmath::{vec3, Matrix3, Matrix4, Vector3, Vector4};
+
+use uniform_buffer::Std140;
+
+uniform_block! {
+    struct Light {
+        direction: Vector3<f32>,
+        intensity: f32,
+        color: Vector3<f32>,
+    }
+}
+
+uniform_block! {
+    struct Weights {
+        count: i32,
+        weights: [f32; 5],
+        last: f32,
+    }
+}
+
+uniform_block! {
+    struct Matrices {
+        scale: f32,
+        normalMatrix: Matrix3<f32>,
+        model: Matrix4<f32>,
+        flag: bool,
+    }
+}
+
+uniform_block! {
+    struct Scene {
+        time: f32,
+        light: Light,
+        lights: [Light; 2],
+        ambient: Vector4<f32>,
+    }
+}
+
+#[test]
+fn vec3_followed_by_float() {
+    // the float fills the 4th component of the vec3, the next vec3 starts at a new vec4
+    assert_eq!(Light::std140_members(), vec![("direction", 0), ("intensity", 12), ("color", 16)]);
+    assert_eq!(Light::SIZE, 32);
+    assert_eq!(Light::ALIGN, 16);
+}
+
+#[test]
+fn float_arrays_have_16_byte_stride() {
+    assert_eq!(<[f32; 5]>::SIZE, 80);
+    assert_eq!(Weights::std140_members(), vec![("count", 0), ("weights", 16), ("last", 96)]);
+    assert_eq!(Weights::SIZE, 112);
+}
+
+#[test]
+fn matrix_columns_are_padded() {
+    assert_eq!(Matrix3::<f32>::SIZE, 48);
+    assert_eq!(Matrix4::<f32>::SIZE, 64);
+    assert_eq!(Matrices::std140_members(), vec![("scale", 0), ("normalMatrix", 16), ("model", 64), ("flag", 128)]);
+    assert_eq!(Matrices::SIZE, 144);
+}
+
+#[test]
+fn nested_blocks() {
+    assert_eq!(Scene::std140_members(), vec![("time", 0), ("light", 16), ("lights", 48), ("ambient", 112)]);
+    assert_eq!(Scene::SIZE, 128);
+}
+
+#[test]
+fn writes_at_padded_offsets() {
+    let light = Light { direction: vec3(1.0, 2.0, 3.0), intensity: 4.0, color: vec3(5.0, 6.0, 7.0) };
+    let mut buffer = vec![0u8; Light::SIZE];
+    light.write_std140(&mut buffer);
+    let floats: Vec<f32> = buffer.chunks(4)
+        .map(|bytes| f32::from_bits(u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])))
+        .collect();
+    assert_eq!(floats, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 0.0]);
+}